@@ -0,0 +1,64 @@
+//! Resolves offsets against the running game and writes them out, e.g.
+//! `cargo run --example export_offsets -- offsets.json offsets.rs offsets.h`
+//!
+//! `--overrides <file.toml|file.json>` pins individual offsets, as `$MAKE_IT_FAIR_OVERRIDES` does.
+//! `--install <dir>` resolves against the libraries of a CS2 install instead of the running game,
+//! leaving out the interfaces that only exist at runtime.
+
+use anyhow::{ensure, Context, Result};
+use make_it_fair::{
+    constant,
+    process::{offline::OfflineSource, offsets::Offsets, overrides::Overrides},
+    Pid, ProcessHandle,
+};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+
+    let mut overrides = Overrides::from_env()?;
+    let mut install = None;
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--overrides" {
+            let path = args.next().context("--overrides needs a file")?;
+            overrides = Overrides::load(path)?;
+        } else if arg == "--install" {
+            install = Some(args.next().context("--install needs a directory")?);
+        } else {
+            paths.push(arg);
+        }
+    }
+
+    ensure!(
+        !paths.is_empty(),
+        "Usage: export_offsets [--overrides <file.toml|file.json>] [--install <dir>] <file.json|file.rs|file.h>..."
+    );
+
+    let offsets = match install {
+        Some(install) => {
+            let source = OfflineSource::from_install(install)?;
+            let mut offsets = Offsets::find_offsets_offline(&source)?;
+            overrides.apply(&mut offsets, &source)?;
+            offsets.report.mark_overridden(overrides.paths());
+            offsets
+        }
+        None => {
+            let process =
+                ProcessHandle::from_pid(Pid::from_process_name(constant::PROCESS_NAME).await?)
+                    .await?;
+            Offsets::find_offsets_with(&process, &overrides)?
+        }
+    };
+
+    print!("{}", offsets.report);
+
+    for path in &paths {
+        offsets.export_to(path)?;
+        println!("Wrote {}", path);
+    }
+
+    Ok(())
+}
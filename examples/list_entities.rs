@@ -0,0 +1,37 @@
+//! Lists every entity in the running game's entity list, e.g.
+//! `cargo run --example list_entities -- weapon_c4` to only show the ones of that class
+//! or designer name
+
+use anyhow::Result;
+use make_it_fair::{constant, Cs2Interface, Pid, ProcessHandle};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+
+    let filter = std::env::args().nth(1);
+
+    let process =
+        ProcessHandle::from_pid(Pid::from_process_name(constant::PROCESS_NAME).await?).await?;
+    let interface = Cs2Interface::new(process)?;
+    let entities = interface.entity_list();
+
+    for entity in entities.iter() {
+        let entity = entity?;
+
+        if filter.as_deref().is_some_and(|filter| !entity.is(filter)) {
+            continue;
+        }
+
+        println!(
+            "{:>5} {:>5} {:#x} {:<32} {}",
+            entity.index,
+            entity.serial,
+            u64::from(entity.address),
+            entity.designer_name.as_deref().unwrap_or("-"),
+            entity.class_name.as_deref().unwrap_or("-"),
+        );
+    }
+
+    Ok(())
+}
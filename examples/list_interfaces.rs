@@ -0,0 +1,29 @@
+//! Lists every interface registered through `CreateInterface` in the running game, e.g.
+//! `cargo run --example list_interfaces -- Cvar` to only show names containing `Cvar`
+
+use anyhow::Result;
+use make_it_fair::{constant, MemorySource, Pid, ProcessHandle};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+
+    let filter = std::env::args().nth(1).unwrap_or_default();
+
+    let process =
+        ProcessHandle::from_pid(Pid::from_process_name(constant::PROCESS_NAME).await?).await?;
+
+    for module in process.list_interfaces()? {
+        println!("{} ({:#x})", module.module, module.base);
+
+        for interface in module
+            .interfaces
+            .iter()
+            .filter(|interface| interface.name.contains(&filter))
+        {
+            println!("    {:<48} {:#x}", interface.name, interface.address);
+        }
+    }
+
+    Ok(())
+}
@@ -6,19 +6,25 @@ use axum::{
     },
     response::IntoResponse,
     routing::get,
-    Router, ServiceExt,
+    Router,
 };
 use log::{error, info};
-use make_it_fair::{constant, cs2_interface::Player, Cs2Interface, Pid, ProcessHandle};
+use make_it_fair::{
+    cs2_interface::Player,
+    game_rules::GameRules,
+    supervisor::{Supervisor, SupervisorConfig, SupervisorState},
+    Cs2Interface,
+};
 use serde::Serialize;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::sync::broadcast;
-use tokio::time::Duration;
 use tower_http::services::ServeDir;
 
 #[derive(Serialize, Clone)]
 struct Payload {
+    status: SupervisorState,
     players: Vec<Player>,
+    game_rules: Option<GameRules>,
 }
 
 #[tokio::main]
@@ -29,16 +35,20 @@ async fn main() -> Result<()> {
     let (tx, _) = broadcast::channel::<Payload>(16);
     let tx = Arc::new(tx);
 
-    let tx_clone = tx.clone();
-
-    let process =
-        ProcessHandle::from_pid(Pid::from_process_name(constant::PROCESS_NAME).await?).await?;
+    let supervisor = Arc::new(Supervisor::new(SupervisorConfig::default()));
 
-    let interface = Cs2Interface::new(process)?;
+    tokio::spawn(forward_status(supervisor.clone(), tx.clone()));
+    // Attaching and polling block, keep them off of the runtime serving clients
+    std::thread::spawn({
+        let supervisor = supervisor.clone();
+        let tx = tx.clone();
 
-    std::thread::spawn(move || {
-        if let Err(e) = cs2_thread(interface, tx_clone) {
-            error!("Error in CS2 thread: {:?}", e);
+        move || {
+            if let Err(e) =
+                supervisor.run(|interface| send_players(interface, supervisor.state(), &tx))
+            {
+                error!("Supervisor stopped: {:#}", e);
+            }
         }
     });
 
@@ -68,25 +78,52 @@ async fn ws_handler(
     ws.on_upgrade(move |socket| handle_socket(socket, tx))
 }
 
-fn cs2_thread(
-    interface: Cs2Interface,
-    tx: Arc<broadcast::Sender<Payload>>,
-) -> Result<(), anyhow::Error> {
+fn send_players(
+    interface: &Cs2Interface,
+    status: SupervisorState,
+    tx: &broadcast::Sender<Payload>,
+) -> Result<()> {
+    if tx.receiver_count() == 0 {
+        return Ok(());
+    }
+
+    let players = interface
+        .get_players()?
+        .into_iter()
+        .filter(|player| player.health > 0)
+        .collect();
+
+    // Players are still worth sending without the match state
+    let game_rules = interface.get_game_rules().unwrap_or_else(|e| {
+        error!("Failed to get game rules: {:#}", e);
+        None
+    });
+
+    if let Err(e) = tx.send(Payload {
+        status,
+        players,
+        game_rules,
+    }) {
+        error!("Failed to send data: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Lets clients know when the game goes away or comes back, since no players are sent meanwhile
+async fn forward_status(supervisor: Arc<Supervisor>, tx: Arc<broadcast::Sender<Payload>>) {
+    let mut state = supervisor.subscribe();
+
+    while state.changed().await.is_ok() {
+        let status = state.borrow_and_update().clone();
 
-    loop {
         if tx.receiver_count() > 0 {
-            let players = interface
-                .get_players()?
-                .into_iter()
-                .filter(|player| player.health > 0)
-                .collect();
-
-            if let Err(e) = tx.send(Payload { players }) {
-                error!("Failed to send data: {}", e);
-            }
+            let _ = tx.send(Payload {
+                status,
+                players: vec![],
+                game_rules: None,
+            });
         }
-
-        std::thread::sleep(Duration::from_millis(100));
     }
 }
 
@@ -0,0 +1,419 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::{
+    constant::CONVAR_OFFSET,
+    process::{memory::Address, remote_ptr::RemotePtr, source::MemorySource},
+};
+
+// `ConVarData` layout
+const NAME: u64 = 0x00;
+const DEFAULT_VALUE: u64 = 0x08;
+const MIN_VALUE: u64 = 0x10;
+const MAX_VALUE: u64 = 0x18;
+const HELP: u64 = 0x20;
+const VALUE_TYPE: u64 = 0x28;
+const FLAGS: u64 = 0x30;
+const VALUE: u64 = CONVAR_OFFSET;
+
+/// `EConVarType`, what a convar's `CVValue_t` holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConVarType {
+    Bool,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+    String,
+    Color,
+    Vector2,
+    Vector3,
+    Vector4,
+    QAngle,
+    Unknown(i16),
+}
+
+impl From<i16> for ConVarType {
+    fn from(value: i16) -> Self {
+        match value {
+            0 => ConVarType::Bool,
+            1 => ConVarType::I16,
+            2 => ConVarType::U16,
+            3 => ConVarType::I32,
+            4 => ConVarType::U32,
+            5 => ConVarType::I64,
+            6 => ConVarType::U64,
+            7 => ConVarType::F32,
+            8 => ConVarType::F64,
+            9 => ConVarType::String,
+            10 => ConVarType::Color,
+            11 => ConVarType::Vector2,
+            12 => ConVarType::Vector3,
+            13 => ConVarType::Vector4,
+            14 => ConVarType::QAngle,
+            value => ConVarType::Unknown(value),
+        }
+    }
+}
+
+/// `FCVAR_*` bits of a convar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ConVarFlags(pub u64);
+
+impl ConVarFlags {
+    pub const DEVELOPMENT_ONLY: u64 = 1 << 1;
+    pub const HIDDEN: u64 = 1 << 4;
+    pub const PROTECTED: u64 = 1 << 5;
+    pub const ARCHIVE: u64 = 1 << 7;
+    pub const NOTIFY: u64 = 1 << 8;
+    pub const REPLICATED: u64 = 1 << 13;
+    pub const CHEAT: u64 = 1 << 14;
+
+    pub fn contains(&self, flag: u64) -> bool {
+        self.0 & flag == flag
+    }
+}
+
+/// A decoded `CVValue_t`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ConVarValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Color([u8; 4]),
+    Vector(Vec<f32>),
+}
+
+impl Display for ConVarValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConVarValue::Bool(value) => write!(f, "{}", *value as u8),
+            ConVarValue::Int(value) => write!(f, "{}", value),
+            ConVarValue::UInt(value) => write!(f, "{}", value),
+            ConVarValue::Float(value) => write!(f, "{}", value),
+            ConVarValue::String(value) => write!(f, "{}", value),
+            ConVarValue::Color([r, g, b, a]) => write!(f, "{} {} {} {}", r, g, b, a),
+            ConVarValue::Vector(values) => {
+                let values: Vec<String> = values.iter().map(f32::to_string).collect();
+                write!(f, "{}", values.join(" "))
+            }
+        }
+    }
+}
+
+/// Rust types a convar's value can be read as, see `Cs2Interface::get_convar`
+pub trait FromConVar: Sized {
+    fn from_convar(value: &ConVarValue) -> Option<Self>;
+}
+
+impl FromConVar for bool {
+    fn from_convar(value: &ConVarValue) -> Option<Self> {
+        match value {
+            ConVarValue::Bool(value) => Some(*value),
+            ConVarValue::Int(value) => Some(*value != 0),
+            ConVarValue::UInt(value) => Some(*value != 0),
+            _ => None,
+        }
+    }
+}
+
+impl FromConVar for f64 {
+    fn from_convar(value: &ConVarValue) -> Option<Self> {
+        match value {
+            ConVarValue::Float(value) => Some(*value),
+            ConVarValue::Int(value) => Some(*value as f64),
+            ConVarValue::UInt(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromConVar for f32 {
+    fn from_convar(value: &ConVarValue) -> Option<Self> {
+        f64::from_convar(value).map(|value| value as f32)
+    }
+}
+
+impl FromConVar for String {
+    fn from_convar(value: &ConVarValue) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+macro_rules! from_convar_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromConVar for $ty {
+                fn from_convar(value: &ConVarValue) -> Option<Self> {
+                    match value {
+                        ConVarValue::Bool(value) => Some(*value as $ty),
+                        ConVarValue::Int(value) => (*value).try_into().ok(),
+                        ConVarValue::UInt(value) => (*value).try_into().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_convar_int!(i16, u16, i32, u32, i64, u64);
+
+/// A registered convar, read in one go.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConVar {
+    pub name: String,
+    pub address: Address,
+    pub value_type: ConVarType,
+    pub flags: ConVarFlags,
+    pub help: Option<String>,
+    pub value: ConVarValue,
+    pub default: Option<ConVarValue>,
+    pub min: Option<ConVarValue>,
+    pub max: Option<ConVarValue>,
+}
+
+impl ConVar {
+    /// Reads the `ConVarData` at `address`
+    pub fn read(source: &impl MemorySource, address: Address) -> Result<Self> {
+        let convar = RemotePtr::new(source, address, "convar");
+
+        let name = convar.field(NAME).deref()?.read_str()?;
+        let value_type = ConVarType::from(convar.value::<i16>(VALUE_TYPE)?);
+
+        let help = match convar.field(HELP).try_deref()? {
+            Some(help) => Some(help.read_str()?).filter(|help| !help.is_empty()),
+            None => None,
+        };
+
+        // Default, min and max point at values of the same type, null when there is none
+        let optional = |offset: u64| -> Result<Option<ConVarValue>> {
+            match convar.field(offset).try_deref()? {
+                Some(value) => read_value(source, value.address(), value_type).map(Some),
+                None => Ok(None),
+            }
+        };
+
+        Ok(Self {
+            name,
+            address,
+            value_type,
+            flags: ConVarFlags(convar.value::<u64>(FLAGS)?),
+            help,
+            value: read_value(source, address + Address::from(VALUE), value_type)?,
+            default: optional(DEFAULT_VALUE)?,
+            min: optional(MIN_VALUE)?,
+            max: optional(MAX_VALUE)?,
+        })
+    }
+
+    /// The value as `T`, failing when the convar holds something that doesn't convert
+    pub fn get<T: FromConVar>(&self) -> Result<T> {
+        match T::from_convar(&self.value) {
+            Some(value) => Ok(value),
+            None => bail!(
+                "Convar {} holds a {:?}, not a {}",
+                self.name,
+                self.value_type,
+                std::any::type_name::<T>()
+            ),
+        }
+    }
+}
+
+/// Decodes the `CVValue_t` at `address`
+fn read_value(
+    source: &impl MemorySource,
+    address: Address,
+    value_type: ConVarType,
+) -> Result<ConVarValue> {
+    let value = RemotePtr::new(source, address, "convar_value");
+
+    Ok(match value_type {
+        ConVarType::Bool => ConVarValue::Bool(value.value::<u8>(0)? != 0),
+        ConVarType::I16 => ConVarValue::Int(value.value::<i16>(0)?.into()),
+        ConVarType::U16 => ConVarValue::UInt(value.value::<u16>(0)?.into()),
+        ConVarType::I32 => ConVarValue::Int(value.value::<i32>(0)?.into()),
+        ConVarType::U32 => ConVarValue::UInt(value.value::<u32>(0)?.into()),
+        ConVarType::I64 => ConVarValue::Int(value.value::<i64>(0)?),
+        ConVarType::U64 => ConVarValue::UInt(value.value::<u64>(0)?),
+        ConVarType::F32 => ConVarValue::Float(value.value::<f32>(0)?.into()),
+        ConVarType::F64 => ConVarValue::Float(value.value::<f64>(0)?),
+        ConVarType::String => {
+            // A `CUtlString`, a pointer that stays null until something is assigned
+            match value.try_deref()? {
+                Some(string) => ConVarValue::String(string.read_str()?),
+                None => ConVarValue::String(String::new()),
+            }
+        }
+        ConVarType::Color => ConVarValue::Color(value.value::<[u8; 4]>(0)?),
+        ConVarType::Vector2 => ConVarValue::Vector(value.value::<[f32; 2]>(0)?.to_vec()),
+        ConVarType::Vector3 | ConVarType::QAngle => {
+            ConVarValue::Vector(value.value::<[f32; 3]>(0)?.to_vec())
+        }
+        ConVarType::Vector4 => ConVarValue::Vector(value.value::<[f32; 4]>(0)?.to_vec()),
+        ConVarType::Unknown(value_type) => bail!("Unknown convar type {}", value_type),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConVar, ConVarFlags, ConVarType, ConVarValue};
+    use crate::{
+        process::{memory::Address, offsets::Offsets, source::test::BufferSource},
+        Cs2Interface,
+    };
+    use anyhow::Result;
+
+    const BASE: u64 = 0x10000;
+
+    /// A convar at `address` with its strings and values laid out behind it
+    fn put_convar(
+        memory: &mut BufferSource,
+        address: u64,
+        name: &str,
+        value_type: i16,
+        value: &[u8],
+    ) {
+        memory.put_u64(address, address + 0x100);
+        memory.put(address + 0x100, format!("{}\0", name).as_bytes());
+        memory.put(address + 0x28, &value_type.to_le_bytes());
+        memory.put(address + 0x40, value);
+    }
+
+    /// The convar interface at BASE, listing `mp_roundtime` (f32, with default, bounds, help and
+    /// flags), `sv_cheats` (bool) and `hostname` (string)
+    fn memory() -> BufferSource {
+        let mut memory = BufferSource::new(BASE, 0x2000);
+
+        memory.put_u64(BASE + 0x40, BASE + 0x200);
+        memory.put_u64(BASE + 0xA0, 3);
+        for (i, convar) in [0x400u64, 0x600, 0x800].iter().enumerate() {
+            memory.put_u64(BASE + 0x200 + i as u64 * 0x10, BASE + convar);
+        }
+
+        let roundtime = BASE + 0x400;
+        put_convar(
+            &mut memory,
+            roundtime,
+            "mp_roundtime",
+            7,
+            &1.92f32.to_le_bytes(),
+        );
+        memory.put(
+            roundtime + 0x30,
+            &(ConVarFlags::REPLICATED | ConVarFlags::NOTIFY).to_le_bytes(),
+        );
+        for (offset, value) in [(0x08, 5.0f32), (0x10, 1.0), (0x18, 60.0)] {
+            let slot = roundtime + 0x180 + offset;
+            memory.put_u64(roundtime + offset, slot);
+            memory.put(slot, &value.to_le_bytes());
+        }
+        memory.put_u64(roundtime + 0x20, roundtime + 0x1C0);
+        memory.put(roundtime + 0x1C0, b"How many minutes each round takes\0");
+
+        put_convar(&mut memory, BASE + 0x600, "sv_cheats", 0, &[1]);
+
+        let hostname = BASE + 0x800;
+        put_convar(
+            &mut memory,
+            hostname,
+            "hostname",
+            9,
+            &(hostname + 0x180).to_le_bytes(),
+        );
+        memory.put(hostname + 0x180, b"Counter-Strike 2\0");
+
+        memory
+    }
+
+    fn interface(memory: BufferSource) -> Result<Cs2Interface<BufferSource>> {
+        let mut offsets = Offsets::default();
+        offsets.interface.convar = Address::from(BASE);
+
+        Cs2Interface::with_offsets(memory, offsets)
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let source = memory();
+        let roundtime = ConVar::read(&source, Address::from(BASE + 0x400))?;
+
+        assert_eq!(roundtime.name, "mp_roundtime");
+        assert_eq!(roundtime.value_type, ConVarType::F32);
+        assert_eq!(roundtime.value, ConVarValue::Float(1.92f32.into()));
+        assert_eq!(roundtime.default, Some(ConVarValue::Float(5.0)));
+        assert_eq!(roundtime.min, Some(ConVarValue::Float(1.0)));
+        assert_eq!(roundtime.max, Some(ConVarValue::Float(60.0)));
+        assert_eq!(
+            roundtime.help.as_deref(),
+            Some("How many minutes each round takes")
+        );
+        assert!(roundtime.flags.contains(ConVarFlags::REPLICATED));
+        assert!(!roundtime.flags.contains(ConVarFlags::CHEAT));
+
+        assert_eq!(roundtime.get::<f32>()?, 1.92);
+        assert!(roundtime.get::<bool>().is_err());
+
+        let cheats = ConVar::read(&source, Address::from(BASE + 0x600))?;
+        assert_eq!(cheats.help, None);
+        assert_eq!(cheats.default, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_interface() -> Result<()> {
+        let interface = interface(memory())?;
+
+        assert_eq!(interface.get_convar::<f32>("mp_roundtime")?, Some(1.92));
+        assert_eq!(interface.get_convar::<bool>("sv_cheats")?, Some(true));
+        assert_eq!(interface.get_convar::<i32>("sv_cheats")?, Some(1));
+        assert_eq!(interface.get_convar::<f32>("not_a_convar")?, None);
+        assert!(interface.get_convar::<i32>("hostname").is_err());
+
+        assert_eq!(
+            interface.get_convar_value_str("hostname")?.as_deref(),
+            Some("Counter-Strike 2")
+        );
+
+        assert_eq!(
+            interface.convar_names(),
+            vec!["hostname", "mp_roundtime", "sv_cheats"]
+        );
+
+        let found = interface.find_convars("ROUND");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "mp_roundtime");
+        assert_eq!(interface.find_convars("").len(), 3);
+
+        Ok(())
+    }
+
+    /// A convar of a type that isn't known is left out of a search rather than failing it
+    #[test]
+    fn test_find_skips_unknown() -> Result<()> {
+        let mut memory = memory();
+        memory.put_u64(BASE + 0xA0, 4);
+        memory.put_u64(BASE + 0x230, BASE + 0xA00);
+        put_convar(&mut memory, BASE + 0xA00, "sv_mystery", 99, &[0]);
+
+        let interface = interface(memory)?;
+
+        assert!(interface.convar("sv_mystery").is_err());
+        assert_eq!(interface.convar_names().len(), 4);
+        assert_eq!(interface.find_convars("").len(), 3);
+        assert!(interface.find_convars("mystery").is_empty());
+
+        Ok(())
+    }
+}
@@ -1,36 +1,48 @@
-use anyhow::{bail, Context, Result};
-use log::info;
+use anyhow::{bail, ensure, Context, Result};
+use log::debug;
 use serde::Serialize;
-use std::collections::HashMap;
-
-use crate::process::{memory::Address, offsets::Offsets, process::ProcessHandle};
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::convar::{ConVar, FromConVar};
+use crate::entity::{EntityHandle, EntityList};
+use crate::game_rules::{GameRules, RoundPhase, GAME_RULES_PROXY, TEAM_MANAGER};
+use crate::process::{
+    memory::{Address, Pod},
+    offset_cache::OffsetCache,
+    offsets::Offsets,
+    overrides::Overrides,
+    process::ProcessHandle,
+    remote_ptr::RemotePtr,
+    source::{MemorySource, ReadBatch},
+};
+use crate::weapon::{Weapon, WeaponCategory};
 
 pub type ControllerAddress = Address;
 pub type PawnAddress = Address;
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
 pub struct Player {
     pub name: String,
     pub health: i32,
-    pub armor: i32,
-    pub money: i32,
+    pub armor: Option<i32>,
+    pub money: Option<i32>,
     pub team: Team,
     pub life_state: LifeState,
-    pub weapon: String,
-    pub weapons: Vec<String>,
-    pub has_defuser: bool,
-    pub has_helmet: bool,
-    pub color: i32,
+    pub weapon: Option<Weapon>,
+    pub weapons: Option<Vec<Weapon>>,
+    pub has_defuser: Option<bool>,
+    pub has_helmet: Option<bool>,
+    pub color: Option<i32>,
     pub position: Vec3,
-    pub rotation: Vec3,
-    pub ping: i32,
-    pub steam_id: u64,
+    pub rotation: Option<Vec3>,
+    pub ping: Option<i32>,
+    pub steam_id: Option<u64>,
     pub active_player: bool,
     pub is_local_player: bool,
 }
 
 #[repr(u8)]
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub enum Team {
     #[default]
     Speactator = 1,
@@ -39,7 +51,7 @@ pub enum Team {
 }
 
 #[repr(u8)]
-#[derive(Debug, Default, Clone, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub enum LifeState {
     Alive, // Alive
     Dying, // Playing death animation falling off a ledge
@@ -49,25 +61,55 @@ pub enum LifeState {
     DiscardBody,
 }
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
-pub struct Cs2Interface {
+// SAFETY: three `f32`s with no padding
+unsafe impl Pod for Vec3 {}
+
+pub struct Cs2Interface<M: MemorySource = ProcessHandle> {
     offsets: Offsets,
-    process_handle: ProcessHandle,
+    process_handle: M,
     convars: HashMap<String, Address>,
+    /// Where the game rules were last found, so the entity list is only walked again once they
+    /// are gone
+    game_rules_entities: RefCell<Option<GameRulesEntities>>,
+}
+
+/// Handles of the game rules proxy and the team managers
+#[derive(Debug, Clone)]
+struct GameRulesEntities {
+    proxy: EntityHandle,
+    teams: Vec<EntityHandle>,
 }
 
-impl Cs2Interface {
-    pub fn new(process_handle: ProcessHandle) -> Result<Self> {
+impl<M: MemorySource> Cs2Interface<M> {
+    /// Resolves offsets against the running game, through the offset cache unless it was turned
+    /// off. An overrides file named by `$MAKE_IT_FAIR_OVERRIDES` bypasses the cache, since the
+    /// cached values may be the ones being corrected.
+    pub fn new(process_handle: M) -> Result<Self> {
+        let overrides = Overrides::from_env()?;
+
+        let offsets = match OffsetCache::from_env() {
+            Some(cache) if overrides.is_empty() => cache.load_or_find(&process_handle)?,
+            _ => Offsets::find_offsets_with(&process_handle, &overrides)?,
+        };
+
+        Self::with_offsets(process_handle, offsets)
+    }
+
+    /// Builds an interface from offsets that have already been resolved, skipping the scan
+    pub fn with_offsets(process_handle: M, offsets: Offsets) -> Result<Self> {
         let mut interface = Cs2Interface {
-            offsets: Offsets::find_offsets(&process_handle)?,
+            offsets,
             process_handle,
             convars: HashMap::new(),
+            game_rules_entities: RefCell::new(None),
         };
 
         interface.set_convars()?;
@@ -75,6 +117,19 @@ impl Cs2Interface {
         Ok(interface)
     }
 
+    pub fn offsets(&self) -> &Offsets {
+        &self.offsets
+    }
+
+    pub fn source(&self) -> &M {
+        &self.process_handle
+    }
+
+    /// Gives back the memory source and the offsets that were in use
+    pub fn into_parts(self) -> (M, Offsets) {
+        (self.process_handle, self.offsets)
+    }
+
     /// Finds all convars and stores them into a map
     fn set_convars(&mut self) -> Result<()> {
         if self.offsets.interface.convar.is_null() {
@@ -82,16 +137,16 @@ impl Cs2Interface {
         }
 
         let offset: u64 = self.offsets.interface.convar.into();
-        let objects = self.process_handle.read_u64(offset + 0x40)?;
+        let objects = self.process_handle.read::<u64>(offset + 0x40)?;
 
-        for i in 0..self.process_handle.read_u64(offset + 0xA0)? {
-            let object = self.process_handle.read_u64_address(objects + i * 0x10)?;
+        for i in 0..self.process_handle.read::<u64>(offset + 0xA0)? {
+            let object = self.process_handle.read::<Address>(objects + i * 0x10)?;
 
             if object.is_null() {
                 break;
             }
 
-            let name_address = self.process_handle.read_u64_address(object)?;
+            let name_address = self.process_handle.read::<Address>(object)?;
             let name = self.process_handle.read_string(name_address)?;
 
             self.convars.insert(name, object);
@@ -100,46 +155,45 @@ impl Cs2Interface {
         Ok(())
     }
 
-    fn get_local_controller(&self) -> Result<ControllerAddress> {
-        self.process_handle
-            .read_u64_address(self.offsets.direct.local_controller)
+    /// The game's entity list, to look entities up by index or walk all of them
+    pub fn entity_list(&self) -> EntityList<'_, M> {
+        EntityList::new(&self.process_handle, self.offsets.interface.entity)
     }
 
-    fn get_pawn(&self, controller: ControllerAddress) -> Result<PawnAddress> {
-        let uhandle =
-            self.process_handle
-                .read_u32(controller + self.offsets.network.controller.m_hPawn)? as u64;
+    /// Starts a pointer chain at `address` inside of the game
+    fn ptr(&self, address: Address, name: &str) -> RemotePtr<'_, M> {
+        RemotePtr::new(&self.process_handle, address, name)
+    }
 
-        let list_entry = self.process_handle.read_u64_address(
-            self.offsets.interface.player
-                + Address::from(0x8) * Address::from((uhandle & 0x7FFF) >> 9),
-        )?;
+    fn get_local_controller(&self) -> Result<ControllerAddress> {
+        self.ptr(self.offsets.direct.local_controller, "local_controller")
+            .cast::<Address>()
+            .read()
+    }
 
-        let pawn = self
-            .process_handle
-            .read_u64_address(list_entry + Address::from(120) * Address::from(uhandle & 0x1FF))?;
+    /// The controller's pawn, `None` once the handle has gone stale
+    fn get_pawn(&self, controller: ControllerAddress) -> Result<Option<PawnAddress>> {
+        let handle = self
+            .ptr(controller, "controller")
+            .value::<EntityHandle>(self.offsets.network.controller.m_hPawn)?;
 
-        Ok(pawn)
+        self.entity_list().resolve(handle)
     }
 
     /// Gets a players name given the controller address
     fn get_name(&self, controller: ControllerAddress) -> Result<Option<String>> {
-        let name_pointer = self
-            .process_handle
-            .read_u64_address(controller + self.offsets.network.controller.m_iszPlayerName)?;
-
-        if name_pointer.is_null() {
-            Ok(None)
-        } else {
-            Ok(Some(self.process_handle.read_string(name_pointer)?))
-        }
+        self.ptr(controller, "controller")
+            .field(self.offsets.network.controller.m_iszPlayerName)
+            .try_deref()?
+            .map(|name| name.read_str())
+            .transpose()
     }
 
     /// Gets a players health given the pawn address
     fn get_health(&self, pawn: PawnAddress) -> Result<i32> {
         let health = self
-            .process_handle
-            .read_i32(pawn + self.offsets.network.pawn.m_iHealth)?;
+            .ptr(pawn, "pawn")
+            .value::<i32>(self.offsets.network.pawn.m_iHealth)?;
 
         if !(0..=100).contains(&health) {
             return Ok(0);
@@ -151,8 +205,8 @@ impl Cs2Interface {
     /// Gets a players health given the pawn address
     fn get_armor(&self, pawn: PawnAddress) -> Result<i32> {
         let armor = self
-            .process_handle
-            .read_i32(pawn + self.offsets.network.pawn.m_ArmorValue)?;
+            .ptr(pawn, "pawn")
+            .value::<i32>(self.offsets.network.pawn.m_ArmorValue)?;
 
         if !(0..=100).contains(&armor) {
             return Ok(0);
@@ -163,17 +217,15 @@ impl Cs2Interface {
 
     /// Gets a players health given the pawn address
     fn get_money(&self, controller: ControllerAddress) -> Result<i32> {
-        let money_services = self.process_handle.read_u64_address(
-            controller + self.offsets.network.controller.m_pInGameMoneyServices,
-        )?;
-
-        if money_services.is_null() {
+        let Some(money_services) = self
+            .ptr(controller, "controller")
+            .field(self.offsets.network.controller.m_pInGameMoneyServices)
+            .try_deref()?
+        else {
             return Ok(0);
-        }
+        };
 
-        let money = self
-            .process_handle
-            .read_i32(money_services + self.offsets.network.money_service.m_iAccount)?;
+        let money = money_services.value::<i32>(self.offsets.network.money_service.m_iAccount)?;
 
         if !(0..=99999).contains(&money) {
             return Ok(0);
@@ -185,8 +237,8 @@ impl Cs2Interface {
     /// Gets a players name given the controller address
     fn get_team(&self, pawn: PawnAddress) -> Result<Option<Team>> {
         let team = self
-            .process_handle
-            .read_u8(pawn + self.offsets.network.pawn.m_iTeamNum)?;
+            .ptr(pawn, "pawn")
+            .value::<u8>(self.offsets.network.pawn.m_iTeamNum)?;
 
         Ok(match team {
             1 => Some(Team::Speactator),
@@ -199,8 +251,8 @@ impl Cs2Interface {
     // Gets the players life state
     fn get_life_state(&self, pawn: PawnAddress) -> Result<Option<LifeState>> {
         let life_state = self
-            .process_handle
-            .read_u8(pawn + self.offsets.network.pawn.m_lifeState)?;
+            .ptr(pawn, "pawn")
+            .value::<u8>(self.offsets.network.pawn.m_lifeState)?;
 
         Ok(match life_state {
             0 => Some(LifeState::Alive),
@@ -212,232 +264,337 @@ impl Cs2Interface {
         })
     }
 
-    // TODO Return Enum
-    fn get_weapon(&self, pawn: PawnAddress) -> Result<Option<String>> {
+    fn get_weapon(&self, pawn: PawnAddress, ammo: Option<&[u16]>) -> Result<Option<Weapon>> {
         // CEntityInstance
-        let weapon_entity_instance = self
-            .process_handle
-            .read_u64_address(pawn + self.offsets.network.pawn.m_pClippingWeapon)?;
+        let Some(weapon_entity_instance) = self
+            .ptr(pawn, "pawn")
+            .field(self.offsets.network.pawn.m_pClippingWeapon)
+            .try_deref()?
+        else {
+            return Ok(None);
+        };
+
+        self.read_weapon(weapon_entity_instance, ammo)
+    }
 
-        if weapon_entity_instance.is_null() {
+    /// Identifies a weapon and reads how loaded it is. Grenade counts come out of the player's
+    /// `ammo` rather than the weapon.
+    fn read_weapon(
+        &self,
+        weapon_instance: RemotePtr<'_, M>,
+        ammo: Option<&[u16]>,
+    ) -> Result<Option<Weapon>> {
+        let Some(mut weapon) = self.identify_weapon(&weapon_instance)? else {
             return Ok(None);
+        };
+
+        let offsets = &self.offsets.network.weapon;
+
+        // -1 for weapons that don't use a magazine
+        weapon.clip = self
+            .if_resolved(&[offsets.m_iClip1], || {
+                weapon_instance.value::<i32>(offsets.m_iClip1)
+            })?
+            .filter(|clip| *clip >= 0);
+
+        if weapon.clip.is_some() {
+            weapon.reserve_ammo = self.if_resolved(&[offsets.m_pReserveAmmo], || {
+                weapon_instance.value::<i32>(offsets.m_pReserveAmmo)
+            })?;
+            weapon.reloading = self.if_resolved(&[offsets.m_bInReload], || {
+                Ok(weapon_instance.value::<u8>(offsets.m_bInReload)? != 0)
+            })?;
         }
 
-        self.get_weapon_name(weapon_entity_instance)
+        if weapon.category == WeaponCategory::Grenade {
+            weapon.count = self
+                .get_ammo_type(&weapon_instance)?
+                .zip(ammo)
+                .and_then(|(ammo_type, ammo)| ammo.get(ammo_type).copied());
+        }
+
+        Ok(Some(weapon))
     }
 
-    // Gets weapon name from the pointer
-    fn get_weapon_name(&self, weapon_instance: Address) -> Result<Option<String>> {
-        // CEntityIdentity, 0x10 = m_pEntity
-        let weapon_entity_identity = self
-            .process_handle
-            .read_u64_address(weapon_instance + Address::from(0x10))
-            .context("Unable to get weapon entity identity")?;
+    /// Where the weapon's ammo is counted in the player's `m_iAmmo`, from its VData's
+    /// `m_nPrimaryAmmoType`. `None` for weapons without ammo, or without the offsets.
+    fn get_ammo_type(&self, weapon_instance: &RemotePtr<'_, M>) -> Result<Option<usize>> {
+        // The subclass' VData pointer isn't a schema field, it is kept right after its ID
+        const SUBCLASS_VDATA: u64 = 0x08;
+
+        let network = &self.offsets.network;
+
+        let ammo_type = self.if_resolved(
+            &[
+                network.weapon.m_nSubclassID,
+                network.weapon_data.m_nPrimaryAmmoType,
+            ],
+            || {
+                let Some(vdata) = weapon_instance
+                    .field(network.weapon.m_nSubclassID + Address::from(SUBCLASS_VDATA))
+                    .try_deref()?
+                else {
+                    return Ok(None);
+                };
+
+                vdata
+                    .value::<i8>(network.weapon_data.m_nPrimaryAmmoType)
+                    .map(Some)
+            },
+        )?;
+
+        // -1 when the weapon takes no ammo
+        Ok(ammo_type
+            .flatten()
+            .and_then(|ammo_type| usize::try_from(ammo_type).ok()))
+    }
+
+    /// The player's `m_iAmmo`, counts of what is carried by ammo type
+    fn get_ammo(&self, pawn: PawnAddress) -> Result<Option<Vec<u16>>> {
+        const AMMO_TYPES: usize = 32;
 
-        if weapon_entity_identity.is_null() {
+        let Some(weapon_services) = self
+            .ptr(pawn, "pawn")
+            .field(self.offsets.network.pawn.m_pWeaponServices)
+            .try_deref()?
+        else {
             return Ok(None);
-        }
+        };
 
-        // 0x20 = m_designerName (pointer -> string)
-        let weapon_name_pointer = self
-            .process_handle
-            .read_u64_address(weapon_entity_identity + Address::from(0x20))
-            .context("Unable to get weapon name pointer")?;
+        let ammo = weapon_services
+            .field(self.offsets.network.weapon_service.m_iAmmo)
+            .address();
+
+        self.process_handle
+            .read_array::<u16>(ammo, AMMO_TYPES)
+            .map(Some)
+    }
+
+    /// Identifies a weapon by its item definition index, or by its designer name when the
+    /// index's offsets weren't found
+    fn identify_weapon(&self, weapon_instance: &RemotePtr<'_, M>) -> Result<Option<Weapon>> {
+        let econ_item = &self.offsets.network.econ_item;
+
+        let id = self.if_resolved(
+            &[
+                econ_item.m_AttributeManager,
+                econ_item.m_Item,
+                econ_item.m_iItemDefinitionIndex,
+            ],
+            || {
+                weapon_instance.value::<u16>(
+                    econ_item.m_AttributeManager
+                        + econ_item.m_Item
+                        + econ_item.m_iItemDefinitionIndex,
+                )
+            },
+        )?;
+
+        let name = self.get_weapon_name(weapon_instance)?;
 
-        if weapon_name_pointer.is_null() {
+        Ok(Weapon::identify(id, name.as_deref()))
+    }
+
+    // Gets weapon name from the pointer
+    fn get_weapon_name(&self, weapon_instance: &RemotePtr<'_, M>) -> Result<Option<String>> {
+        // CEntityIdentity, 0x10 = m_pEntity
+        let Some(weapon_entity_identity) = weapon_instance.field(0x10).try_deref()? else {
             return Ok(None);
-        }
+        };
 
-        Ok(Some(
-            self.process_handle
-                .read_string(weapon_name_pointer)
-                .context("UNable to read weapon name from pointer")?,
-        ))
+        // 0x20 = m_designerName (pointer -> string)
+        weapon_entity_identity
+            .field(0x20)
+            .try_deref()?
+            .map(|name| name.read_str())
+            .transpose()
     }
 
     // Gets all weapons given the pawn
-    fn get_weapons(&self, pawn: PawnAddress) -> Result<Vec<String>> {
-        let weapon_services = self
-            .process_handle
-            .read_u64_address(pawn + self.offsets.network.pawn.m_pWeaponServices)?;
-
-        if weapon_services.is_null() {
+    fn get_weapons(&self, pawn: PawnAddress, ammo: Option<&[u16]>) -> Result<Vec<Weapon>> {
+        let Some(weapon_services) = self
+            .ptr(pawn, "pawn")
+            .field(self.offsets.network.pawn.m_pWeaponServices)
+            .try_deref()?
+        else {
             return Ok(vec![]);
-        }
+        };
 
         // 8 bytes size, 8 bytes pointer to data
-        let size = self
-            .process_handle
-            .read_u64(weapon_services + self.offsets.network.weapon_service.m_hMyWeapons)?;
-
-        let weapon_vector = self.process_handle.read_u64(
-            weapon_services
-                + self.offsets.network.weapon_service.m_hMyWeapons
-                + Address::from(0x08),
-        )?;
+        let my_weapons = weapon_services.field(self.offsets.network.weapon_service.m_hMyWeapons);
+        let size = my_weapons.value::<u64>(0)?;
+        let weapon_vector = my_weapons.value::<u64>(0x08)?;
 
-        let mut weapon_names = vec![];
+        // A garbage size would otherwise turn into a huge allocation
+        ensure!(size <= 64, "Implausible weapon count {}", size);
 
-        for i in 0..size {
-            // weird bit-fuckery, why exactly does it need the & 0xfff?
-            // No idea. - Isaac
-            let weapon_index = self.process_handle.read_u32(weapon_vector + i * 0x04)? & 0xfff;
-
-            let weapon_entity = self.get_client_entity(weapon_index as u64)?;
+        let weapon_handles = self
+            .process_handle
+            .read_array::<EntityHandle>(weapon_vector, size as usize)?;
 
-            if let Some(entity) = weapon_entity {
-                let weapon_name = self.get_weapon_name(entity)?;
+        let entities = self.entity_list();
+        let mut weapons = vec![];
 
-                if let Some(weapon_name) = weapon_name {
-                    weapon_names.push(weapon_name);
+        for weapon_handle in weapon_handles {
+            if let Some(entity) = entities.resolve(weapon_handle)? {
+                if let Some(weapon) = self.read_weapon(self.ptr(entity, "weapon"), ammo)? {
+                    weapons.push(weapon);
                 }
             }
         }
 
-        Ok(weapon_names)
+        Ok(weapons)
     }
 
-    fn get_client_entity(&self, index: impl Into<Address>) -> Result<Option<Address>> {
-        let index = index.into();
+    /// Resolves many entity indices at once. Chunk pointers and slots are each fetched with a
+    /// single batched read instead of two reads per index.
+    fn get_client_entities(&self, indices: &[u64]) -> Result<Vec<Option<Address>>> {
+        let mut chunks = ReadBatch::new();
+
+        for &index in indices {
+            chunks.push(
+                self.offsets.interface.entity
+                    + Address::from(0x08) * Address::from(index >> 9)
+                    + Address::from(0x10),
+                8,
+            );
+        }
 
-        // wtf is this doing, and how?
-        let v1 = self.process_handle.read_u64_address(
-            self.offsets.interface.entity
-                + Address::from(0x08) * (index >> 9)
-                + Address::from(0x10),
-        )?;
+        self.process_handle.read_batch(&mut chunks);
 
-        if v1.is_null() {
-            return Ok(None);
-        }
+        let mut slots = ReadBatch::new();
+        let mut slot_indices = Vec::with_capacity(indices.len());
 
-        // what?
-        let entity = self
-            .process_handle
-            .read_u64_address(v1 + Address::from(120) * (index & Address::from(0x1ff)))?;
+        for (i, &index) in indices.iter().enumerate() {
+            let chunk = chunks
+                .get_u64(i)
+                .with_context(|| format!("Unable to read entity chunk for index {}", index))?;
 
-        if entity.is_null() {
-            return Ok(None);
+            slot_indices.push((chunk != 0).then(|| slots.push(chunk + 120 * (index & 0x1ff), 8)));
         }
 
-        Ok(Some(entity))
-    }
+        self.process_handle.read_batch(&mut slots);
 
-    /// Gets a players health given the pawn address
-    fn get_defuser(&self, pawn: PawnAddress) -> Result<bool> {
-        let item_services = self
-            .process_handle
-            .read_u64_address(pawn + self.offsets.network.pawn.m_pItemServices)?;
+        slot_indices
+            .into_iter()
+            .zip(indices)
+            .map(|(slot, index)| {
+                let Some(slot) = slot else {
+                    return Ok(None);
+                };
 
-        if item_services.is_null() {
-            return Ok(false);
-        }
+                let entity = slots
+                    .get_u64(slot)
+                    .with_context(|| format!("Unable to read entity slot {}", index))?;
 
-        Ok(self
-            .process_handle
-            .read_u8(item_services + self.offsets.network.item_service.m_bHasDefuser)?
-            != 0)
+                Ok(Address::from(entity).non_null())
+            })
+            .collect()
     }
 
-    // Returns if a player has a helmet or not
-    fn get_helmet(&self, pawn: PawnAddress) -> Result<bool> {
-        let item_services = self
-            .process_handle
-            .read_u64_address(pawn + self.offsets.network.pawn.m_pItemServices)?;
+    /// Returns whether the player has a defuser and a helmet, both live in the item services
+    fn get_items(&self, pawn: PawnAddress) -> Result<(Option<bool>, Option<bool>)> {
+        let item_service = &self.offsets.network.item_service;
 
-        if item_services.is_null() {
-            return Ok(false);
+        if self.offsets.network.pawn.m_pItemServices.is_null() {
+            return Ok((None, None));
         }
 
-        Ok(self
-            .process_handle
-            .read_u8(item_services + self.offsets.network.item_service.m_bHasHelmet)?
-            != 0)
+        let Some(item_services) = self
+            .ptr(pawn, "pawn")
+            .field(self.offsets.network.pawn.m_pItemServices)
+            .try_deref()?
+        else {
+            return Ok((Some(false), Some(false)));
+        };
+
+        let has_item = |offset: Address| {
+            self.if_resolved(&[offset], || Ok(item_services.value::<u8>(offset)? != 0))
+        };
+
+        Ok((
+            has_item(item_service.m_bHasDefuser)?,
+            has_item(item_service.m_bHasHelmet)?,
+        ))
     }
 
     fn get_color(&self, controller: ControllerAddress) -> Result<i32> {
-        self.process_handle
-            .read_i32(controller + self.offsets.network.controller.m_iCompTeammateColor)
+        self.ptr(controller, "controller")
+            .value::<i32>(self.offsets.network.controller.m_iCompTeammateColor)
     }
 
     fn get_position(&self, pawn: PawnAddress) -> Result<Vec3> {
         // 3 32-bit floats
-        let position = pawn + self.offsets.network.pawn.m_vOldOrigin;
-
-        Ok(Vec3 {
-            x: self.process_handle.read_f32(position)?,
-            y: self
-                .process_handle
-                .read_f32(position + Address::from(0x04))?,
-            z: self
-                .process_handle
-                .read_f32(position + Address::from(0x08))?,
-        })
+        self.ptr(pawn, "pawn")
+            .value(self.offsets.network.pawn.m_vOldOrigin)
     }
 
     fn get_rotation(&self, pawn: PawnAddress) -> Result<Vec3> {
-        let position = pawn + self.offsets.network.pawn.m_angEyeAngles;
-
-        Ok(Vec3 {
-            x: self.process_handle.read_f32(position)?,
-            y: self
-                .process_handle
-                .read_f32(position + Address::from(0x04))?,
-            z: self
-                .process_handle
-                .read_f32(position + Address::from(0x08))?,
-        })
+        self.ptr(pawn, "pawn")
+            .value(self.offsets.network.pawn.m_angEyeAngles)
     }
 
     fn get_ping(&self, controller: ControllerAddress) -> Result<i32> {
-        self.process_handle
-            .read_i32(controller + self.offsets.network.controller.m_iPing)
+        self.ptr(controller, "controller")
+            .value::<i32>(self.offsets.network.controller.m_iPing)
     }
 
     fn get_steam_id(&self, controller: ControllerAddress) -> Result<u64> {
-        self.process_handle
-            .read_u64(controller + self.offsets.network.controller.m_steamID)
+        self.ptr(controller, "controller")
+            .value::<u64>(self.offsets.network.controller.m_steamID)
     }
 
     fn get_spectator_target(&self, pawn: PawnAddress) -> Result<Option<PawnAddress>> {
-        let observer_services = self
-            .process_handle
-            .read_u64_address(pawn + self.offsets.network.pawn.m_pObserverServices)?;
-
-        if observer_services.is_null() {
+        if self.offsets.network.pawn.m_pObserverServices.is_null()
+            || self
+                .offsets
+                .network
+                .observer_service
+                .m_hObserverTarget
+                .is_null()
+        {
             return Ok(None);
         }
 
-        let target = self.process_handle.read_u32(
-            observer_services + self.offsets.network.observer_service.m_hObserverTarget,
-        )? & 0x7fff;
-
-        if target == 0 {
+        let Some(observer_services) = self
+            .ptr(pawn, "pawn")
+            .field(self.offsets.network.pawn.m_pObserverServices)
+            .try_deref()?
+        else {
             return Ok(None);
-        }
+        };
 
-        let v2 = self.process_handle.read_u64(
-            self.offsets.interface.player + Address::from(8) * (Address::from(target as u64) >> 9),
-        )?;
+        let target = observer_services
+            .value::<EntityHandle>(self.offsets.network.observer_service.m_hObserverTarget)?;
 
-        if v2 == 0 {
+        // Index 0 is the world, which nobody spectates
+        if target.index() == 0 {
             return Ok(None);
         }
 
-        let entity = self
-            .process_handle
-            .read_u64_address(v2 + 120 * (target as u64 & 0x1ff))?;
+        self.entity_list().resolve(target)
+    }
 
-        if entity.is_null() {
+    /// Runs a getter only if every offset it reads through was found, optional offsets that are
+    /// missing leave their `Player` field empty instead of reading from `address + 0`
+    fn if_resolved<T>(
+        &self,
+        offsets: &[Address],
+        get: impl FnOnce() -> Result<T>,
+    ) -> Result<Option<T>> {
+        if offsets.iter().any(|offset| offset.is_null()) {
             return Ok(None);
         }
 
-        Ok(Some(entity))
+        get().map(Some)
     }
 
     fn get_player(&self, controller: ControllerAddress) -> Result<Option<Player>> {
+        let network = &self.offsets.network;
         let mut player = Player::default();
-        let pawn = self.get_pawn(controller)?;
+        let Some(pawn) = self.get_pawn(controller)? else {
+            return Ok(None);
+        };
 
         let team = match self.get_team(pawn)? {
             Some(team) => team,
@@ -452,44 +609,67 @@ impl Cs2Interface {
             .get_health(pawn)
             .context("Unable to get player's health")?;
         player.armor = self
-            .get_armor(pawn)
+            .if_resolved(&[network.pawn.m_ArmorValue], || self.get_armor(pawn))
             .context("Unable to get player's armor")?;
         player.money = self
-            .get_money(controller)
+            .if_resolved(
+                &[
+                    network.controller.m_pInGameMoneyServices,
+                    network.money_service.m_iAccount,
+                ],
+                || self.get_money(controller),
+            )
             .context("Unable to get player's money")?;
         player.team = team;
         player.life_state = self
             .get_life_state(pawn)
             .context("Unable to get player's life state")?
             .unwrap_or_default();
+        let ammo = self
+            .if_resolved(
+                &[
+                    network.pawn.m_pWeaponServices,
+                    network.weapon_service.m_iAmmo,
+                ],
+                || self.get_ammo(pawn),
+            )
+            .context("Unable to get player's ammo")?
+            .flatten();
         player.weapon = self
-            .get_weapon(pawn)
-            .ok()
-            .flatten()
-            .unwrap_or("Unknown".to_string());
+            .if_resolved(&[network.pawn.m_pClippingWeapon], || {
+                Ok(self.get_weapon(pawn, ammo.as_deref()).ok().flatten())
+            })?
+            .flatten();
         player.weapons = self
-            .get_weapons(pawn)
+            .if_resolved(
+                &[
+                    network.pawn.m_pWeaponServices,
+                    network.weapon_service.m_hMyWeapons,
+                ],
+                || self.get_weapons(pawn, ammo.as_deref()),
+            )
             .context("Unable to get player's weapons")?;
-        player.has_defuser = self
-            .get_defuser(pawn)
-            .context("Unable to determine if player has defuser")?;
-        player.has_helmet = self
-            .get_helmet(pawn)
-            .context("Unable to determine if player has helmet")?;
+        (player.has_defuser, player.has_helmet) = self
+            .get_items(pawn)
+            .context("Unable to get player's items")?;
         player.color = self
-            .get_color(controller)
+            .if_resolved(&[network.controller.m_iCompTeammateColor], || {
+                self.get_color(controller)
+            })
             .context("Unable to get player's color")?;
         player.position = self
             .get_position(pawn)
             .context("Unable to get player's position")?;
         player.rotation = self
-            .get_rotation(pawn)
+            .if_resolved(&[network.pawn.m_angEyeAngles], || self.get_rotation(pawn))
             .context("Unable to get player's rotation")?;
         player.ping = self
-            .get_ping(controller)
+            .if_resolved(&[network.controller.m_iPing], || self.get_ping(controller))
             .context("Unable to get player's ping")?;
         player.steam_id = self
-            .get_steam_id(controller)
+            .if_resolved(&[network.controller.m_steamID], || {
+                self.get_steam_id(controller)
+            })
             .context("Unable to get player's Steam ID")?;
 
         Ok(Some(player))
@@ -497,26 +677,26 @@ impl Cs2Interface {
 
     pub fn get_players(&self) -> Result<Vec<Player>> {
         let local_controller = self.get_local_controller()?;
-        let local_pawn = self.get_pawn(local_controller)?;
-
-        let spectator_target = self.get_spectator_target(local_pawn)?;
+        let spectator_target = match self.get_pawn(local_controller)? {
+            Some(local_pawn) => self.get_spectator_target(local_pawn)?,
+            None => None,
+        };
 
         let mut players = vec![];
 
-        for i in 1..=64 {
-            let controller = match self
-                .get_client_entity(i)
-                .context("Unable to get client entity")?
-            {
-                Some(controller) => controller,
-                None => {
-                    continue;
-                }
+        let indices: Vec<u64> = (1..=64).collect();
+        let controllers = self
+            .get_client_entities(&indices)
+            .context("Unable to get client entities")?;
+
+        for controller in controllers {
+            let Some(controller) = controller else {
+                continue;
             };
 
             let pawn = match self.get_pawn(controller) {
-                Ok(pawn) => pawn,
-                Err(_) => continue,
+                Ok(Some(pawn)) => pawn,
+                _ => continue,
             };
 
             let mut player = match self
@@ -543,16 +723,195 @@ impl Cs2Interface {
         Ok(players)
     }
 
-    pub fn get_convar_value_str(&self, convar: &str) -> Result<Option<String>> {
-        let convar = *match self.convars.get(convar) {
-            Some(address) => address,
-            None => return Ok(None),
+    /// Reads the state of the match from the game rules and team entities. `None` when there is
+    /// no game rules entity, such as in the main menu.
+    pub fn get_game_rules(&self) -> Result<Option<GameRules>> {
+        let network = &self.offsets.network;
+
+        if network.game_rules_proxy.m_pGameRules.is_null() {
+            return Ok(None);
+        }
+
+        let Some((proxy, teams)) = self.find_game_rules_entities() else {
+            return Ok(None);
+        };
+
+        let Some(rules) = self
+            .ptr(proxy, "game_rules_proxy")
+            .field(network.game_rules_proxy.m_pGameRules)
+            .try_deref()?
+        else {
+            return Ok(None);
+        };
+
+        let offsets = &network.game_rules;
+
+        let round = self
+            .if_resolved(&[offsets.m_totalRoundsPlayed], || {
+                Ok(rules.value::<i32>(offsets.m_totalRoundsPlayed)? + 1)
+            })
+            .context("Unable to get the round")?;
+        let phase = self
+            .if_resolved(
+                &[
+                    offsets.m_bWarmupPeriod,
+                    offsets.m_bFreezePeriod,
+                    offsets.m_iRoundWinStatus,
+                ],
+                || {
+                    Ok(RoundPhase::from_state(
+                        rules.value::<u8>(offsets.m_bWarmupPeriod)? != 0,
+                        rules.value::<u8>(offsets.m_bFreezePeriod)? != 0,
+                        rules.value::<i32>(offsets.m_iRoundWinStatus)?,
+                    ))
+                },
+            )
+            .context("Unable to get the round phase")?;
+        let paused = self
+            .if_resolved(
+                &[offsets.m_bGamePaused, offsets.m_bMatchWaitingForResume],
+                || {
+                    Ok(rules.value::<u8>(offsets.m_bGamePaused)? != 0
+                        || rules.value::<u8>(offsets.m_bMatchWaitingForResume)? != 0)
+                },
+            )
+            .context("Unable to get whether the match is paused")?;
+        let round_start_time = self
+            .if_resolved(&[offsets.m_fRoundStartTime], || {
+                rules.value::<f32>(offsets.m_fRoundStartTime)
+            })
+            .context("Unable to get the round start time")?;
+        let round_time = self
+            .if_resolved(&[offsets.m_iRoundTime], || {
+                rules.value::<i32>(offsets.m_iRoundTime)
+            })
+            .context("Unable to get the round time")?;
+
+        let mut terrorist_score = None;
+        let mut counter_terrorist_score = None;
+
+        for team in teams {
+            let team = self.ptr(team, "team");
+
+            let Some((number, score)) = self
+                .if_resolved(&[network.team.m_iTeamNum, network.team.m_iScore], || {
+                    Ok((
+                        team.value::<u8>(network.team.m_iTeamNum)?,
+                        team.value::<i32>(network.team.m_iScore)?,
+                    ))
+                })
+                .context("Unable to get a team's score")?
+            else {
+                break;
+            };
+
+            match number {
+                2 => terrorist_score = Some(score),
+                3 => counter_terrorist_score = Some(score),
+                _ => {}
+            }
+        }
+
+        Ok(Some(GameRules {
+            round,
+            phase,
+            paused,
+            round_start_time,
+            round_time,
+            terrorist_score,
+            counter_terrorist_score,
+        }))
+    }
+
+    /// The addresses of the game rules proxy and the team managers. The handles they were found
+    /// under last time are checked first, the entity list is only walked again when one of them
+    /// no longer resolves.
+    fn find_game_rules_entities(&self) -> Option<(Address, Vec<Address>)> {
+        let list = self.entity_list();
+        let resolve = |handle| list.resolve(handle).ok().flatten();
+
+        if let Some(cached) = self.game_rules_entities.borrow().as_ref() {
+            let proxy = resolve(cached.proxy);
+            let teams: Option<Vec<_>> = cached.teams.iter().map(|team| resolve(*team)).collect();
+
+            if let (Some(proxy), Some(teams)) = (proxy, teams) {
+                return Some((proxy, teams));
+            }
+        }
+
+        let mut proxy = None;
+        let mut teams = vec![];
+
+        for entity in list.iter() {
+            // Entities come and go while the list is walked, one that can't be read isn't fatal
+            let Ok(entity) = entity else {
+                continue;
+            };
+
+            if entity.is(GAME_RULES_PROXY) {
+                proxy = Some(entity);
+            } else if entity.is(TEAM_MANAGER) {
+                teams.push(entity);
+            }
+        }
+
+        let Some(proxy) = proxy else {
+            self.game_rules_entities.replace(None);
+            return None;
         };
 
-        let offset = convar + Address::from(64);
+        self.game_rules_entities.replace(Some(GameRulesEntities {
+            proxy: proxy.handle(),
+            teams: teams.iter().map(|team| team.handle()).collect(),
+        }));
+
+        Some((
+            proxy.address,
+            teams.into_iter().map(|team| team.address).collect(),
+        ))
+    }
+
+    /// Reads a convar in full, `None` if no convar has that name
+    pub fn convar(&self, name: &str) -> Result<Option<ConVar>> {
+        match self.convars.get(name) {
+            Some(address) => ConVar::read(&self.process_handle, *address).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads a convar's current value as `T`, e.g. `get_convar::<f32>("mp_roundtime")`. Fails
+    /// when the convar's type doesn't convert to `T`.
+    pub fn get_convar<T: FromConVar>(&self, name: &str) -> Result<Option<T>> {
+        self.convar(name)?.map(|convar| convar.get()).transpose()
+    }
+
+    /// The value of a convar of any type, formatted the way the console prints it
+    pub fn get_convar_value_str(&self, convar: &str) -> Result<Option<String>> {
+        Ok(self.convar(convar)?.map(|convar| convar.value.to_string()))
+    }
 
-        let value = self.process_handle.read_string(offset)?;
+    /// Names of every registered convar, sorted
+    pub fn convar_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.convars.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
 
-        Ok(Some(value))
+    /// Reads every convar whose name contains `query`, ignoring case. An empty query lists them
+    /// all. Convars that can't be read or decoded are logged and left out.
+    pub fn find_convars(&self, query: &str) -> Vec<ConVar> {
+        let query = query.to_lowercase();
+
+        self.convar_names()
+            .into_iter()
+            .filter(|name| name.to_lowercase().contains(&query))
+            .filter_map(|name| match self.convar(name) {
+                Ok(convar) => convar,
+                Err(e) => {
+                    debug!("Skipping convar {}: {:#}", name, e);
+                    None
+                }
+            })
+            .collect()
     }
 }
@@ -0,0 +1,521 @@
+use anyhow::{ensure, Context, Result};
+use log::debug;
+use serde::Serialize;
+
+use crate::process::{
+    memory::{self, Address, Pod},
+    source::MemorySource,
+};
+
+/// The entity list is `ENTITY_CHUNKS` pointers to chunks of `CHUNK_ENTITIES` identities each
+pub const ENTITY_CHUNKS: u64 = 64;
+pub const CHUNK_ENTITIES: u64 = 512;
+pub const MAX_ENTITIES: u64 = ENTITY_CHUNKS * CHUNK_ENTITIES;
+
+/// `CGameEntitySystem`'s first chunk pointer
+const FIRST_CHUNK: u64 = 0x10;
+
+// `CEntityIdentity` layout
+const IDENTITY_SIZE: u64 = 120;
+const INSTANCE: u64 = 0x00;
+const CLASS_INFO: u64 = 0x08;
+const HANDLE: u64 = 0x10;
+const DESIGNER_NAME: u64 = 0x20;
+
+/// `CEntityClass::m_pSchemaClassBinding`, then `SchemaClassInfoData_t::m_pszName`
+const CLASS_BINDING: u64 = 0x30;
+const CLASS_NAME: u64 = 0x08;
+
+/// The low 15 bits of a handle are the index, the rest the serial
+const INDEX_MASK: u32 = 0x7FFF;
+const SERIAL_SHIFT: u32 = 15;
+
+/// A `CHandle`: an index into the entity list, plus the serial of the entity that held the slot
+/// when the handle was taken. Slots get reused, so a handle whose serial no longer matches points
+/// at whatever took the old entity's place.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct EntityHandle(pub u32);
+
+// SAFETY: a plain `u32`
+unsafe impl Pod for EntityHandle {}
+
+impl EntityHandle {
+    /// What an unset handle holds
+    pub const INVALID: EntityHandle = EntityHandle(u32::MAX);
+
+    pub fn new(index: u32, serial: u32) -> Self {
+        Self((serial << SERIAL_SHIFT) | (index & INDEX_MASK))
+    }
+
+    pub fn index(self) -> u32 {
+        self.0 & INDEX_MASK
+    }
+
+    pub fn serial(self) -> u32 {
+        self.0 >> SERIAL_SHIFT
+    }
+
+    /// Whether the handle points at a slot at all, stale or not
+    pub fn is_valid(self) -> bool {
+        self != Self::INVALID && self.index() != INDEX_MASK
+    }
+}
+
+impl From<u32> for EntityHandle {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+/// One live entity in the entity list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entity {
+    pub index: u32,
+    pub serial: u32,
+    /// The entity instance, what netvar offsets are relative to
+    pub address: Address,
+    /// Its `CEntityIdentity` inside of the list
+    pub identity: Address,
+    /// e.g. `weapon_ak47`, `cs_player_controller`
+    pub designer_name: Option<String>,
+    /// The schema class, e.g. `C_AK47`, `CCSPlayerController`
+    pub class_name: Option<String>,
+}
+
+impl Entity {
+    pub fn handle(&self) -> EntityHandle {
+        EntityHandle::new(self.index, self.serial)
+    }
+
+    /// Whether `name` is either the class or the designer name
+    pub fn is(&self, name: &str) -> bool {
+        self.class_name.as_deref() == Some(name) || self.designer_name.as_deref() == Some(name)
+    }
+}
+
+/// The chunked list of `CEntityIdentity`s hanging off of `CGameEntitySystem`
+pub struct EntityList<'a, M: MemorySource> {
+    source: &'a M,
+    list: Address,
+}
+
+impl<'a, M: MemorySource> EntityList<'a, M> {
+    pub fn new(source: &'a M, list: Address) -> Self {
+        Self { source, list }
+    }
+
+    /// Checks that `list` is laid out like an entity list: its first chunk is allocated and every
+    /// occupied identity in it holds a handle to its own index. Neighbouring objects may well be
+    /// readable pointers too, this tells them apart.
+    pub fn validate(&self) -> Result<()> {
+        let chunk = self
+            .source
+            .read::<u64>(self.chunk_pointer(0))
+            .context("Unable to read the first chunk pointer")?;
+        ensure!(chunk != 0, "The first chunk is not allocated");
+
+        let identities = self
+            .source
+            .read_bytes(chunk, IDENTITY_SIZE * CHUNK_ENTITIES)
+            .context("Unable to read the first chunk")?;
+
+        let mut occupied = 0;
+        for (index, identity) in identities.chunks_exact(IDENTITY_SIZE as usize).enumerate() {
+            if memory::read_pod_at::<u64>(identity, INSTANCE).unwrap_or_default() == 0 {
+                continue;
+            }
+
+            let handle = memory::read_pod_at(identity, HANDLE).unwrap_or(EntityHandle::INVALID);
+            ensure!(
+                handle.index() as usize == index,
+                "Identity {} holds a handle to {}",
+                index,
+                handle.index()
+            );
+
+            occupied += 1;
+        }
+
+        ensure!(occupied > 0, "The first chunk holds no entities");
+
+        Ok(())
+    }
+
+    /// Where the pointer to the chunk holding `index` lives
+    fn chunk_pointer(&self, index: u64) -> u64 {
+        u64::from(self.list) + FIRST_CHUNK + 0x08 * (index / CHUNK_ENTITIES)
+    }
+
+    /// The `CEntityIdentity` of `index`, `None` if its chunk isn't allocated
+    pub fn identity(&self, index: u64) -> Result<Option<Address>> {
+        let chunk = self.source.read::<u64>(self.chunk_pointer(index))?;
+
+        if chunk == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Address::from(
+            chunk + IDENTITY_SIZE * (index % CHUNK_ENTITIES),
+        )))
+    }
+
+    /// The entity instance at `index`, `None` if the slot is empty
+    pub fn get(&self, index: u64) -> Result<Option<Address>> {
+        let Some(identity) = self.identity(index)? else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .source
+            .read::<Address>(identity + Address::from(INSTANCE))?
+            .non_null())
+    }
+
+    /// The entity instance a handle points at. `None` for an unset handle, an empty slot, or one
+    /// that has since been given to another entity.
+    pub fn resolve(&self, handle: EntityHandle) -> Result<Option<Address>> {
+        if !handle.is_valid() {
+            return Ok(None);
+        }
+
+        let Some(identity) = self.identity(handle.index() as u64)? else {
+            return Ok(None);
+        };
+
+        let current = self
+            .source
+            .read::<EntityHandle>(identity + Address::from(HANDLE))?;
+
+        if current.serial() != handle.serial() {
+            debug!(
+                "Stale handle to entity {}, serial {} is now {}",
+                handle.index(),
+                handle.serial(),
+                current.serial()
+            );
+            return Ok(None);
+        }
+
+        Ok(self
+            .source
+            .read::<Address>(identity + Address::from(INSTANCE))?
+            .non_null())
+    }
+
+    /// Walks every allocated chunk, reading each one in a single go
+    pub fn iter(&self) -> Entities<'_, 'a, M> {
+        Entities {
+            list: self,
+            chunk: 0,
+            base: 0,
+            slots: vec![],
+            slot: 0,
+        }
+    }
+
+    /// Every entity whose class or designer name is `name`
+    pub fn of_class<'b>(&'b self, name: &'b str) -> impl Iterator<Item = Result<Entity>> + 'b {
+        self.iter().filter(move |entity| match entity {
+            Ok(entity) => entity.is(name),
+            Err(_) => true,
+        })
+    }
+
+    /// Builds an `Entity` out of the identity's bytes
+    fn entity(&self, index: u64, identity: u64, bytes: &[u8]) -> Result<Option<Entity>> {
+        let field = |offset: u64| {
+            u64::from_le_bytes(
+                bytes[offset as usize..offset as usize + 8]
+                    .try_into()
+                    .unwrap(),
+            )
+        };
+
+        let address = field(INSTANCE);
+        if address == 0 {
+            return Ok(None);
+        }
+
+        let handle = EntityHandle(field(HANDLE) as u32);
+
+        let designer_name = match field(DESIGNER_NAME) {
+            0 => None,
+            name => Some(self.source.read_string(name)?),
+        };
+
+        let class_name = self
+            .class_name(field(CLASS_INFO))
+            .with_context(|| format!("Unable to read the class of entity {}", index))?;
+
+        Ok(Some(Entity {
+            index: index as u32,
+            serial: handle.serial(),
+            address: address.into(),
+            identity: identity.into(),
+            designer_name,
+            class_name,
+        }))
+    }
+
+    fn class_name(&self, class_info: u64) -> Result<Option<String>> {
+        if class_info == 0 {
+            return Ok(None);
+        }
+
+        let binding = self.source.read::<u64>(class_info + CLASS_BINDING)?;
+        if binding == 0 {
+            return Ok(None);
+        }
+
+        let name = self.source.read::<u64>(binding + CLASS_NAME)?;
+        if name == 0 {
+            return Ok(None);
+        }
+
+        self.source.read_string(name).map(Some)
+    }
+}
+
+/// Iterator over the live entities of an `EntityList`, in index order. A chunk or entity that
+/// can't be read comes out as an error and the walk carries on after it.
+pub struct Entities<'l, 'a, M: MemorySource> {
+    list: &'l EntityList<'a, M>,
+    chunk: u64,
+    /// Where the current chunk starts, and its identities
+    base: u64,
+    slots: Vec<u8>,
+    slot: u64,
+}
+
+impl<M: MemorySource> Entities<'_, '_, M> {
+    /// Loads the next allocated chunk, `false` once there are none left
+    fn next_chunk(&mut self) -> Result<bool> {
+        while self.chunk < ENTITY_CHUNKS {
+            let chunk = self.chunk;
+            self.chunk += 1;
+            self.slot = 0;
+            self.slots.clear();
+
+            let address = self
+                .list
+                .source
+                .read::<u64>(self.list.chunk_pointer(chunk * CHUNK_ENTITIES))?;
+
+            if address == 0 {
+                continue;
+            }
+
+            self.slots = self
+                .list
+                .source
+                .read_bytes(address, IDENTITY_SIZE * CHUNK_ENTITIES)
+                .with_context(|| format!("Unable to read entity chunk {}", chunk))?;
+            self.base = address;
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+}
+
+impl<M: MemorySource> Iterator for Entities<'_, '_, M> {
+    type Item = Result<Entity>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.slots.is_empty() || self.slot == CHUNK_ENTITIES {
+                match self.next_chunk() {
+                    Ok(true) => {}
+                    Ok(false) => return None,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            let slot = self.slot;
+            self.slot += 1;
+
+            let start = (slot * IDENTITY_SIZE) as usize;
+            let bytes = &self.slots[start..start + IDENTITY_SIZE as usize];
+
+            let index = (self.chunk - 1) * CHUNK_ENTITIES + slot;
+            let identity = self.base + slot * IDENTITY_SIZE;
+
+            match self.list.entity(index, identity, bytes) {
+                Ok(Some(entity)) => return Some(Ok(entity)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{
+        EntityHandle, EntityList, CHUNK_ENTITIES, CLASS_INFO, DESIGNER_NAME, FIRST_CHUNK, HANDLE,
+        IDENTITY_SIZE, INSTANCE,
+    };
+    use crate::process::{memory::Address, source::test::BufferSource};
+    use anyhow::Result;
+    use std::ops::{Deref, DerefMut};
+
+    const BASE: u64 = 0x10000;
+    const LIST: u64 = BASE;
+    /// Chunks 0 and 2 are allocated, chunk 1 isn't
+    const CHUNK_0: u64 = BASE + 0x1000;
+    const CHUNK_2: u64 = BASE + 0x20000;
+    const CLASS: u64 = BASE + 0x40000;
+    const STRINGS: u64 = BASE + 0x40100;
+
+    /// A fake game laid out in one flat block of memory
+    pub(crate) struct World(pub BufferSource);
+
+    impl Deref for World {
+        type Target = BufferSource;
+
+        fn deref(&self) -> &BufferSource {
+            &self.0
+        }
+    }
+
+    impl DerefMut for World {
+        fn deref_mut(&mut self) -> &mut BufferSource {
+            &mut self.0
+        }
+    }
+
+    impl World {
+        pub fn new(base: u64, size: usize) -> Self {
+            Self(BufferSource::new(base, size))
+        }
+
+        /// Points the entity list at `list` to the chunk holding entity `index`
+        pub fn put_chunk(&mut self, list: u64, index: u64, chunk: u64) {
+            self.put_u64(list + FIRST_CHUNK + 0x08 * (index / CHUNK_ENTITIES), chunk);
+        }
+
+        /// Fills the identity of entity `index` in its chunk, `name` points at its designer name.
+        /// Returns the identity's address.
+        pub fn spawn(
+            &mut self,
+            chunk: u64,
+            index: u64,
+            serial: u32,
+            instance: u64,
+            name: u64,
+        ) -> u64 {
+            let identity = chunk + IDENTITY_SIZE * (index % CHUNK_ENTITIES);
+
+            self.put_u64(identity + INSTANCE, instance);
+            self.put_u32(identity + HANDLE, EntityHandle::new(index as u32, serial).0);
+            self.put_u64(identity + DESIGNER_NAME, name);
+
+            identity
+        }
+    }
+
+    fn world() -> World {
+        let mut world = World::new(BASE, 0x41000);
+
+        world.put_chunk(LIST, 0, CHUNK_0);
+        world.put_chunk(LIST, 1024, CHUNK_2);
+
+        // CEntityClass -> binding -> name
+        world.put_u64(CLASS + 0x30, CLASS + 0x80);
+        world.put_u64(CLASS + 0x88, STRINGS);
+        world.put(STRINGS, b"C_BaseEntity\0");
+        world.put(STRINGS + 0x20, b"cs_player_controller\0");
+        world.put(STRINGS + 0x40, b"weapon_c4\0");
+
+        // All three share the one class
+        for (chunk, index, serial, instance, name) in [
+            (CHUNK_0, 1, 3, 0xA000, STRINGS + 0x20),
+            (CHUNK_0, 511, 0, 0xB000, 0),
+            (CHUNK_2, 1030, 7, 0xC000, STRINGS + 0x40),
+        ] {
+            let identity = world.spawn(chunk, index, serial, instance, name);
+            world.put_u64(identity + CLASS_INFO, CLASS);
+        }
+
+        world
+    }
+
+    #[test]
+    fn test_entity_list() -> Result<()> {
+        let world = world();
+        let list = EntityList::new(&*world, LIST.into());
+
+        let entities = list.iter().collect::<Result<Vec<_>>>()?;
+        assert_eq!(entities.len(), 3);
+
+        assert_eq!(entities[0].index, 1);
+        assert_eq!(entities[0].serial, 3);
+        assert_eq!(entities[0].address, Address::from(0xA000));
+        assert_eq!(entities[0].identity, Address::from(CHUNK_0 + IDENTITY_SIZE));
+        assert_eq!(
+            entities[0].designer_name.as_deref(),
+            Some("cs_player_controller")
+        );
+        assert_eq!(entities[0].class_name.as_deref(), Some("C_BaseEntity"));
+
+        assert_eq!(entities[1].index, 511);
+        assert_eq!(entities[1].designer_name, None);
+
+        assert_eq!(entities[2].index, 1030);
+        assert_eq!(entities[2].serial, 7);
+
+        let bombs = list.of_class("weapon_c4").collect::<Result<Vec<_>>>()?;
+        assert_eq!(bombs.len(), 1);
+        assert_eq!(bombs[0].address, Address::from(0xC000));
+        assert_eq!(list.of_class("C_BaseEntity").count(), 3);
+
+        assert_eq!(list.get(1030)?, Some(Address::from(0xC000)));
+        assert_eq!(list.get(2)?, None);
+        assert_eq!(list.get(600)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handles() -> Result<()> {
+        let world = world();
+        let list = EntityList::new(&*world, LIST.into());
+
+        let handle = EntityHandle((7 << 15) | 1030);
+        assert_eq!(handle.index(), 1030);
+        assert_eq!(handle.serial(), 7);
+        assert_eq!(list.resolve(handle)?, Some(Address::from(0xC000)));
+
+        let entities = list.iter().collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            list.resolve(entities[0].handle())?,
+            Some(Address::from(0xA000))
+        );
+
+        // The slot has since been handed to an entity with another serial
+        assert_eq!(list.resolve(EntityHandle::new(1030, 6))?, None);
+        // Empty slot, unallocated chunk, unset handle
+        assert_eq!(list.resolve(EntityHandle::new(2, 0))?, None);
+        assert_eq!(list.resolve(EntityHandle::new(600, 0))?, None);
+        assert_eq!(list.resolve(EntityHandle::INVALID)?, None);
+        assert!(!EntityHandle::new(0x7FFF, 0).is_valid());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut world = world();
+        assert!(EntityList::new(&*world, LIST.into()).validate().is_ok());
+
+        // An occupied identity holding a handle to another slot
+        world.put_u32(CHUNK_0 + IDENTITY_SIZE + HANDLE, EntityHandle::new(2, 3).0);
+        assert!(EntityList::new(&*world, LIST.into()).validate().is_err());
+
+        // No first chunk
+        world.put_chunk(LIST, 0, 0);
+        assert!(EntityList::new(&*world, LIST.into()).validate().is_err());
+    }
+}
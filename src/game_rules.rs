@@ -0,0 +1,68 @@
+use serde::Serialize;
+
+/// Designer names of the entities match state is read from
+pub const GAME_RULES_PROXY: &str = "cs_gamerules";
+pub const TEAM_MANAGER: &str = "cs_team_manager";
+
+/// Where in a round the match is
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RoundPhase {
+    #[default]
+    Warmup,
+    /// Players are frozen in spawn while buying
+    FreezeTime,
+    Live,
+    /// The round has been won, the next one hasn't started yet
+    RoundOver,
+}
+
+impl RoundPhase {
+    /// Warmup wins over freeze time, which wins over a decided round
+    pub fn from_state(warmup: bool, freeze_period: bool, round_win_status: i32) -> Self {
+        if warmup {
+            RoundPhase::Warmup
+        } else if freeze_period {
+            RoundPhase::FreezeTime
+        } else if round_win_status != 0 {
+            RoundPhase::RoundOver
+        } else {
+            RoundPhase::Live
+        }
+    }
+}
+
+/// The state of the match, read from `C_CSGameRules` and the team entities. Fields whose offsets
+/// weren't found are left empty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GameRules {
+    /// The round being played, starting at 1
+    pub round: Option<i32>,
+    pub phase: Option<RoundPhase>,
+    /// Paused by an admin, a timeout or waiting on players to resume
+    pub paused: Option<bool>,
+    /// Game time the round started at, or will once freeze time is over
+    pub round_start_time: Option<f32>,
+    /// How long the round lasts, in seconds
+    pub round_time: Option<i32>,
+    pub terrorist_score: Option<i32>,
+    pub counter_terrorist_score: Option<i32>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::RoundPhase;
+
+    #[test]
+    fn test_phase() {
+        assert_eq!(RoundPhase::from_state(true, true, 0), RoundPhase::Warmup);
+        assert_eq!(
+            RoundPhase::from_state(false, true, 0),
+            RoundPhase::FreezeTime
+        );
+        assert_eq!(RoundPhase::from_state(false, false, 0), RoundPhase::Live);
+        assert_eq!(
+            RoundPhase::from_state(false, false, 2),
+            RoundPhase::RoundOver
+        );
+    }
+}
@@ -1,8 +1,14 @@
 pub mod constant;
+pub mod convar;
 pub mod cs2_interface;
+pub mod entity;
+pub mod game_rules;
 pub mod process;
+pub mod supervisor;
+pub mod weapon;
 
 pub use process::pid::Pid;
 pub use process::process::ProcessHandle;
+pub use process::source::MemorySource;
 
 pub use cs2_interface::Cs2Interface;
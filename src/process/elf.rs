@@ -0,0 +1,1307 @@
+use std::path::Path;
+
+use anyhow::{bail, ensure, Context, Result};
+
+use super::{
+    memory::{self, Pod},
+    source::MemorySource,
+};
+
+pub const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
+const ELF_CLASS_64: u8 = 2;
+const ELF_DATA_LSB: u8 = 1;
+
+// Program header types
+pub const PT_LOAD: u32 = 1;
+pub const PT_DYNAMIC: u32 = 2;
+pub const PT_NOTE: u32 = 4;
+
+// Program header flags
+pub const PF_X: u32 = 1;
+pub const PF_W: u32 = 2;
+pub const PF_R: u32 = 4;
+
+// Dynamic section tags
+pub const DT_NULL: i64 = 0;
+pub const DT_HASH: i64 = 4;
+pub const DT_STRTAB: i64 = 5;
+pub const DT_SYMTAB: i64 = 6;
+pub const DT_STRSZ: i64 = 10;
+pub const DT_RELA: i64 = 7;
+pub const DT_RELASZ: i64 = 8;
+pub const DT_RELAENT: i64 = 9;
+pub const DT_SYMENT: i64 = 11;
+pub const DT_RELRSZ: i64 = 35;
+pub const DT_RELR: i64 = 36;
+pub const DT_GNU_HASH: i64 = 0x6fff_fef5;
+
+// Relocation types
+pub const R_X86_64_RELATIVE: u32 = 8;
+
+// Note types
+pub const NT_GNU_BUILD_ID: u32 = 3;
+
+/// Upper bound on program headers, anything above this is a corrupt header
+const MAX_PROGRAM_HEADERS: u16 = 256;
+
+/// Upper bound on dynamic entries walked before giving up on finding `DT_NULL`
+const MAX_DYNAMIC_ENTRIES: u64 = 4096;
+
+/// Upper bound on relocations read from `DT_RELA`, anything above this is a corrupt table
+const MAX_RELOCATIONS: u64 = 0x100_0000;
+
+/// Upper bound on symbol names, the string table size is used when it is smaller
+const MAX_SYMBOL_NAME: u64 = 4096;
+
+/// `Elf64_Ehdr`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ElfHeader {
+    pub e_ident: [u8; 16],
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: u64,
+    pub e_phoff: u64,
+    pub e_shoff: u64,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
+/// `Elf64_Phdr`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramHeader {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_paddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+    pub p_align: u64,
+}
+
+/// `Elf64_Dyn`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicEntry {
+    pub d_tag: i64,
+    pub d_val: u64,
+}
+
+/// `Elf64_Sym`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RawSymbol {
+    pub st_name: u32,
+    pub st_info: u8,
+    pub st_other: u8,
+    pub st_shndx: u16,
+    pub st_value: u64,
+    pub st_size: u64,
+}
+
+/// `Elf64_Nhdr`, followed by the name and descriptor, each padded to 4 bytes
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NoteHeader {
+    pub n_namesz: u32,
+    pub n_descsz: u32,
+    pub n_type: u32,
+}
+
+/// `Elf64_Rela`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Relocation {
+    pub r_offset: u64,
+    pub r_info: u64,
+    pub r_addend: i64,
+}
+
+impl Relocation {
+    pub fn relocation_type(&self) -> u32 {
+        (self.r_info & 0xffff_ffff) as u32
+    }
+
+    pub fn symbol_index(&self) -> u32 {
+        (self.r_info >> 32) as u32
+    }
+}
+
+// SAFETY: all six are `#[repr(C)]`, made of integers, and laid out without padding
+unsafe impl Pod for NoteHeader {}
+unsafe impl Pod for Relocation {}
+unsafe impl Pod for ElfHeader {}
+unsafe impl Pod for ProgramHeader {}
+unsafe impl Pod for DynamicEntry {}
+unsafe impl Pod for RawSymbol {}
+
+impl ProgramHeader {
+    pub fn is_executable(&self) -> bool {
+        self.p_flags & PF_X != 0
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.p_flags & PF_W != 0
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.p_flags & PF_R != 0
+    }
+
+    /// End of the segment once loaded, `None` for a corrupt header that wraps the address space
+    pub fn end(&self) -> Option<u64> {
+        self.p_vaddr.checked_add(self.p_memsz)
+    }
+
+    /// Whether a virtual address lies inside of this segment once loaded
+    pub fn contains(&self, vaddr: u64) -> bool {
+        vaddr >= self.p_vaddr && self.end().is_some_and(|end| vaddr < end)
+    }
+
+    /// Whether `size` bytes starting at a virtual address all lie inside of this segment
+    pub fn contains_range(&self, vaddr: u64, size: u64) -> Result<bool> {
+        let end = vaddr.checked_add(size).context("Address out of range")?;
+
+        Ok(self.contains(vaddr) && self.end().is_some_and(|segment_end| end <= segment_end))
+    }
+}
+
+/// Byte access to an ELF image by virtual address, relative to the address the image is loaded at.
+pub trait ElfData {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<()>;
+}
+
+impl<T: ElfData + ?Sized> ElfData for &T {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<()> {
+        (**self).read_vaddr(vaddr, buffer)
+    }
+}
+
+/// An image laid out the way the loader maps it (a module dump), indexed by virtual address.
+impl ElfData for [u8] {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<()> {
+        let start = usize::try_from(vaddr).context("Address out of range")?;
+        let bytes = start
+            .checked_add(buffer.len())
+            .and_then(|end| self.get(start..end))
+            .with_context(|| {
+                format!(
+                    "Read of {} bytes at {:#x} is outside of the {} byte image",
+                    buffer.len(),
+                    vaddr,
+                    self.len()
+                )
+            })?;
+
+        buffer.copy_from_slice(bytes);
+
+        Ok(())
+    }
+}
+
+impl ElfData for Vec<u8> {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<()> {
+        self.as_slice().read_vaddr(vaddr, buffer)
+    }
+}
+
+/// An image as it is stored on disk, indexed by virtual address through its `PT_LOAD` segments.
+/// The part of a segment past its file size reads as zeroes, like the loader's `.bss`.
+pub struct FileImage {
+    data: Vec<u8>,
+    segments: Vec<ProgramHeader>,
+}
+
+impl FileImage {
+    /// Takes the `PT_LOAD` segments from the program headers, which are located by file offset
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        let header: ElfHeader = read_pod(&data, 0).context("Unable to read ELF header")?;
+
+        ensure!(header.e_ident[..4] == ELF_MAGIC, "Invalid ELF Header");
+        ensure!(
+            header.e_phnum <= MAX_PROGRAM_HEADERS,
+            "Program header count {} is out of range",
+            header.e_phnum
+        );
+
+        let mut program_headers = vec![ProgramHeader::zeroed(); header.e_phnum as usize];
+        data.as_slice()
+            .read_vaddr(header.e_phoff, memory::bytes_of_mut(&mut program_headers))
+            .context("Unable to read program headers")?;
+
+        let segments = program_headers
+            .into_iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+            .collect();
+
+        Ok(Self { data, segments })
+    }
+
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data =
+            std::fs::read(path).with_context(|| format!("Unable to read '{}'", path.display()))?;
+
+        Self::new(data).with_context(|| format!("'{}' is not an ELF image", path.display()))
+    }
+
+    /// Offset into the file holding a virtual address, `None` for `.bss` and unmapped addresses
+    pub fn file_offset(&self, vaddr: u64) -> Option<u64> {
+        self.segments
+            .iter()
+            .find(|ph| {
+                vaddr >= ph.p_vaddr
+                    && ph
+                        .p_vaddr
+                        .checked_add(ph.p_filesz)
+                        .is_some_and(|file_end| vaddr < file_end)
+            })
+            .and_then(|ph| (vaddr - ph.p_vaddr).checked_add(ph.p_offset))
+    }
+}
+
+impl ElfData for FileImage {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<()> {
+        let size = buffer.len() as u64;
+        let mut segment = None;
+
+        for ph in &self.segments {
+            if ph.contains_range(vaddr, size)? {
+                segment = Some(ph);
+                break;
+            }
+        }
+
+        let segment = segment.with_context(|| {
+            format!(
+                "Read of {} bytes at {:#x} is outside of every loaded segment",
+                buffer.len(),
+                vaddr
+            )
+        })?;
+
+        // Bytes up to the file size come from the file, the rest is zero filled
+        let file_end = segment
+            .p_vaddr
+            .checked_add(segment.p_filesz)
+            .context("Segment file size out of range")?;
+        let in_file = file_end.saturating_sub(vaddr).min(size) as usize;
+        let (from_file, zeroed) = buffer.split_at_mut(in_file);
+
+        if !from_file.is_empty() {
+            let offset = (vaddr - segment.p_vaddr)
+                .checked_add(segment.p_offset)
+                .context("Segment file offset out of range")?;
+            self.data.as_slice().read_vaddr(offset, from_file)?;
+        }
+        zeroed.fill(0);
+
+        Ok(())
+    }
+}
+
+/// A module mapped into a target, read through its `MemorySource`.
+pub struct LoadedModule<'a, M: MemorySource> {
+    source: &'a M,
+    base: u64,
+}
+
+impl<'a, M: MemorySource> LoadedModule<'a, M> {
+    pub fn new(source: &'a M, base: u64) -> Self {
+        Self { source, base }
+    }
+}
+
+impl<M: MemorySource> ElfData for LoadedModule<'_, M> {
+    fn read_vaddr(&self, vaddr: u64, buffer: &mut [u8]) -> Result<()> {
+        let address = self
+            .base
+            .checked_add(vaddr)
+            .context("Address out of range")?;
+
+        self.source.read_into(address, buffer)
+    }
+}
+
+/// A symbol from the dynamic symbol table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+    pub info: u8,
+    pub other: u8,
+    pub section_index: u16,
+}
+
+/// `STT_*` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolType {
+    NoType,
+    Object,
+    Function,
+    Section,
+    File,
+    Common,
+    Tls,
+    IndirectFunction,
+    Other(u8),
+}
+
+/// `STB_*` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolBinding {
+    Local,
+    Global,
+    Weak,
+    Unique,
+    Other(u8),
+}
+
+impl Symbol {
+    fn from_raw(raw: &RawSymbol, name: String) -> Self {
+        Self {
+            name,
+            value: raw.st_value,
+            size: raw.st_size,
+            info: raw.st_info,
+            other: raw.st_other,
+            section_index: raw.st_shndx,
+        }
+    }
+
+    pub fn symbol_type(&self) -> SymbolType {
+        match self.info & 0xf {
+            0 => SymbolType::NoType,
+            1 => SymbolType::Object,
+            2 => SymbolType::Function,
+            3 => SymbolType::Section,
+            4 => SymbolType::File,
+            5 => SymbolType::Common,
+            6 => SymbolType::Tls,
+            10 => SymbolType::IndirectFunction,
+            other => SymbolType::Other(other),
+        }
+    }
+
+    pub fn binding(&self) -> SymbolBinding {
+        match self.info >> 4 {
+            0 => SymbolBinding::Local,
+            1 => SymbolBinding::Global,
+            2 => SymbolBinding::Weak,
+            10 => SymbolBinding::Unique,
+            other => SymbolBinding::Other(other),
+        }
+    }
+
+    /// Defined symbols live in a section of this module, undefined ones are imports
+    pub fn is_defined(&self) -> bool {
+        self.section_index != 0
+    }
+}
+
+/// The `DT_GNU_HASH` table
+#[derive(Debug, Clone, Copy)]
+pub struct GnuHashTable {
+    pub bucket_count: u32,
+    pub symbol_offset: u32,
+    pub bloom_size: u32,
+    pub bloom_shift: u32,
+    pub bloom_vaddr: u64,
+    pub buckets_vaddr: u64,
+    pub chains_vaddr: u64,
+}
+
+impl GnuHashTable {
+    /// Address of the chain entry for a symbol at or past `symbol_offset`
+    fn chain_vaddr(&self, index: u64) -> Result<u64> {
+        index
+            .checked_sub(self.symbol_offset as u64)
+            .and_then(|chain| chain.checked_mul(4))
+            .and_then(|offset| self.chains_vaddr.checked_add(offset))
+            .with_context(|| format!("Hash chain index {} is out of range", index))
+    }
+}
+
+/// The `DT_HASH` table
+#[derive(Debug, Clone, Copy)]
+pub struct SysvHashTable {
+    pub bucket_count: u32,
+    pub chain_count: u32,
+    pub buckets_vaddr: u64,
+    pub chains_vaddr: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HashTable {
+    Gnu(GnuHashTable),
+    Sysv(SysvHashTable),
+}
+
+/// A parsed ELF image: the header, program headers and dynamic section are read up front, the
+/// symbol and string tables are read on demand.
+pub struct Elf<D: ElfData> {
+    data: D,
+    base: u64,
+    header: ElfHeader,
+    program_headers: Vec<ProgramHeader>,
+    dynamic: Vec<DynamicEntry>,
+}
+
+impl<'a, M: MemorySource> Elf<LoadedModule<'a, M>> {
+    /// Parses a module loaded at `base` inside of a target.
+    pub fn from_module(source: &'a M, base: u64) -> Result<Self> {
+        Self::parse(LoadedModule::new(source, base), base)
+    }
+}
+
+impl Elf<FileImage> {
+    /// Parses an image straight from a file on disk
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(FileImage::read_file(path)?, 0)
+    }
+}
+
+impl<D: ElfData> Elf<D> {
+    /// Parses an image. `base` is the address the image was loaded at, the loader rewrites
+    /// dynamic pointers to absolute addresses so they are rebased against it. Use 0 for images
+    /// that were never loaded.
+    pub fn parse(data: D, base: u64) -> Result<Self> {
+        let header: ElfHeader = read_pod(&data, 0).context("Unable to read ELF header")?;
+
+        ensure!(header.e_ident[..4] == ELF_MAGIC, "Invalid ELF Header");
+        ensure!(
+            header.e_ident[4] == ELF_CLASS_64 && header.e_ident[5] == ELF_DATA_LSB,
+            "Only 64-bit little endian ELF images are supported"
+        );
+        ensure!(
+            header.e_phentsize as usize == std::mem::size_of::<ProgramHeader>(),
+            "Unexpected program header entry size {}",
+            header.e_phentsize
+        );
+        ensure!(
+            header.e_phnum <= MAX_PROGRAM_HEADERS,
+            "Program header count {} is out of range",
+            header.e_phnum
+        );
+
+        let mut program_headers = vec![ProgramHeader::zeroed(); header.e_phnum as usize];
+        data.read_vaddr(header.e_phoff, memory::bytes_of_mut(&mut program_headers))
+            .context("Unable to read program headers")?;
+
+        let mut elf = Self {
+            data,
+            base,
+            header,
+            program_headers,
+            dynamic: vec![],
+        };
+
+        elf.dynamic = elf.read_dynamic()?;
+
+        Ok(elf)
+    }
+
+    fn read_dynamic(&self) -> Result<Vec<DynamicEntry>> {
+        let Some(segment) = self.segment(PT_DYNAMIC) else {
+            return Ok(vec![]);
+        };
+
+        ensure!(
+            segment.end().is_some(),
+            "PT_DYNAMIC segment is out of range"
+        );
+
+        let entry_size = std::mem::size_of::<DynamicEntry>() as u64;
+        let capacity = (segment.p_memsz / entry_size).min(MAX_DYNAMIC_ENTRIES);
+
+        let mut entries = vec![];
+
+        for i in 0..capacity {
+            let entry: DynamicEntry = read_pod(&self.data, segment.p_vaddr + i * entry_size)
+                .context("Unable to read dynamic entry")?;
+
+            if entry.d_tag == DT_NULL {
+                break;
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn header(&self) -> &ElfHeader {
+        &self.header
+    }
+
+    pub fn program_headers(&self) -> &[ProgramHeader] {
+        &self.program_headers
+    }
+
+    pub fn dynamic(&self) -> &[DynamicEntry] {
+        &self.dynamic
+    }
+
+    /// First program header of a given type
+    pub fn segment(&self, p_type: u32) -> Option<&ProgramHeader> {
+        self.program_headers.iter().find(|ph| ph.p_type == p_type)
+    }
+
+    pub fn load_segments(&self) -> impl Iterator<Item = &ProgramHeader> {
+        self.program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+    }
+
+    /// Size of the image once mapped, from the end of the highest `PT_LOAD` segment
+    pub fn image_size(&self) -> u64 {
+        self.load_segments()
+            .filter_map(ProgramHeader::end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The `NT_GNU_BUILD_ID` note the linker stamps into the image, unique to each build
+    pub fn build_id(&self) -> Result<Option<Vec<u8>>> {
+        for note in self
+            .program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_NOTE)
+        {
+            let end = note.end().context("PT_NOTE segment is out of range")?;
+            let mut position = note.p_vaddr;
+
+            while end - position >= std::mem::size_of::<NoteHeader>() as u64 {
+                let header = read_pod::<NoteHeader>(&self.data, position)?;
+                let name = position + std::mem::size_of::<NoteHeader>() as u64;
+                let Some((descriptor, next)) = name
+                    .checked_add(align4(header.n_namesz))
+                    .and_then(|descriptor| {
+                        let next = descriptor.checked_add(align4(header.n_descsz))?;
+                        Some((descriptor, next))
+                    })
+                    .filter(|&(_, next)| next <= end)
+                else {
+                    break;
+                };
+                position = next;
+
+                if header.n_type != NT_GNU_BUILD_ID || header.n_namesz != 4 {
+                    continue;
+                }
+
+                let mut owner = [0u8; 4];
+                self.data.read_vaddr(name, &mut owner)?;
+
+                if &owner == b"GNU\0" {
+                    let mut id = vec![0u8; header.n_descsz as usize];
+                    self.data.read_vaddr(descriptor, &mut id)?;
+
+                    return Ok(Some(id));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Entries of the `DT_RELA` table, which the loader applies when mapping the image
+    pub fn relocations(&self) -> Result<Vec<Relocation>> {
+        let (Some(table), Some(size)) =
+            (self.dynamic_pointer(DT_RELA), self.dynamic_value(DT_RELASZ))
+        else {
+            return Ok(vec![]);
+        };
+
+        let entry_size = self
+            .dynamic_value(DT_RELAENT)
+            .unwrap_or(std::mem::size_of::<Relocation>() as u64);
+        ensure!(
+            entry_size == std::mem::size_of::<Relocation>() as u64,
+            "Unexpected relocation entry size {}",
+            entry_size
+        );
+
+        let count = size / entry_size;
+        ensure!(
+            count <= MAX_RELOCATIONS,
+            "Relocation count {} is out of range",
+            count
+        );
+
+        let mut relocations = vec![Relocation::zeroed(); count as usize];
+        self.data
+            .read_vaddr(table, memory::bytes_of_mut(&mut relocations))
+            .context("Unable to read relocations")?;
+
+        Ok(relocations)
+    }
+
+    /// Addresses the `DT_RELR` table relocates, each holding a pointer the loader adds the base to
+    pub fn relr_offsets(&self) -> Result<Vec<u64>> {
+        let (Some(table), Some(size)) =
+            (self.dynamic_pointer(DT_RELR), self.dynamic_value(DT_RELRSZ))
+        else {
+            return Ok(vec![]);
+        };
+
+        let count = size / 8;
+        ensure!(
+            count <= MAX_RELOCATIONS,
+            "Relocation count {} is out of range",
+            count
+        );
+
+        let mut entries = vec![0u64; count as usize];
+        self.data
+            .read_vaddr(table, memory::bytes_of_mut(&mut entries))
+            .context("Unable to read relocations")?;
+
+        // An even entry is an address, an odd one a bitmap of the 63 words following the last
+        let mut offsets = vec![];
+        let mut next = 0;
+
+        for entry in entries {
+            if entry & 1 == 0 {
+                offsets.push(entry);
+                next = entry + 8;
+            } else {
+                for bit in 1..64 {
+                    if entry >> bit & 1 != 0 {
+                        offsets.push(next + (bit - 1) * 8);
+                    }
+                }
+
+                next += 63 * 8;
+            }
+        }
+
+        Ok(offsets)
+    }
+
+    /// Raw value of the first dynamic entry with a given tag
+    pub fn dynamic_value(&self, tag: i64) -> Option<u64> {
+        self.dynamic
+            .iter()
+            .find(|entry| entry.d_tag == tag)
+            .map(|entry| entry.d_val)
+    }
+
+    /// Value of a pointer dynamic entry as a virtual address relative to the image
+    pub fn dynamic_pointer(&self, tag: i64) -> Option<u64> {
+        self.dynamic_value(tag).map(|value| self.rebase(value))
+    }
+
+    /// Turns a pointer the loader may have relocated back into an image relative address
+    pub fn rebase(&self, pointer: u64) -> u64 {
+        if self.base != 0 && pointer >= self.base {
+            pointer - self.base
+        } else {
+            pointer
+        }
+    }
+
+    /// Whether `size` bytes at a virtual address fall inside a single `PT_LOAD` segment
+    fn is_loaded(&self, vaddr: u64, size: u64) -> Result<bool> {
+        for segment in self.load_segments() {
+            if segment.contains_range(vaddr, size)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Reads a plain-old-data value at a virtual address, which must fall inside a `PT_LOAD`
+    /// segment
+    pub fn read<T: Pod>(&self, vaddr: u64) -> Result<T> {
+        let size = std::mem::size_of::<T>() as u64;
+
+        ensure!(
+            self.is_loaded(vaddr, size)?,
+            "Read of {} bytes at {:#x} is outside of every loaded segment",
+            size,
+            vaddr
+        );
+
+        read_pod(&self.data, vaddr)
+    }
+
+    /// Reads a NUL terminated string of at most `max_length` bytes
+    pub fn read_string(&self, vaddr: u64, max_length: u64) -> Result<String> {
+        let segment = self
+            .load_segments()
+            .find(|ph| ph.contains(vaddr))
+            .with_context(|| format!("String at {:#x} is outside of every segment", vaddr))?;
+
+        const CHUNK_SIZE: u64 = 128;
+
+        let limit = max_length.min(segment.p_vaddr + segment.p_memsz - vaddr);
+        let mut bytes = vec![];
+
+        while (bytes.len() as u64) < limit {
+            let start = vaddr + bytes.len() as u64;
+            let length = CHUNK_SIZE.min(limit - bytes.len() as u64) as usize;
+            let mut chunk = [0u8; CHUNK_SIZE as usize];
+            self.data.read_vaddr(start, &mut chunk[..length])?;
+
+            if let Some(end) = chunk[..length].iter().position(|&c| c == 0) {
+                bytes.extend_from_slice(&chunk[..end]);
+                return Ok(bytes.iter().map(|&c| c as char).collect());
+            }
+
+            bytes.extend_from_slice(&chunk[..length]);
+        }
+
+        bail!("String at {:#x} is not terminated", vaddr)
+    }
+
+    /// Reads a name out of the dynamic string table
+    pub fn string_at(&self, offset: u32) -> Result<String> {
+        let string_table = self
+            .dynamic_pointer(DT_STRTAB)
+            .context("Missing DT_STRTAB")?;
+        let string_table_size = self.dynamic_value(DT_STRSZ).context("Missing DT_STRSZ")?;
+
+        ensure!(
+            (offset as u64) < string_table_size,
+            "String offset {:#x} is past the string table",
+            offset
+        );
+
+        self.read_string(
+            string_table + offset as u64,
+            (string_table_size - offset as u64).min(MAX_SYMBOL_NAME),
+        )
+    }
+
+    pub fn hash_table(&self) -> Result<Option<HashTable>> {
+        if let Some(vaddr) = self.dynamic_pointer(DT_GNU_HASH) {
+            let [bucket_count, symbol_offset, bloom_size, bloom_shift] =
+                self.read::<[u32; 4]>(vaddr)?;
+
+            ensure!(bucket_count > 0, "DT_GNU_HASH has no buckets");
+
+            let bloom_vaddr = vaddr + 16;
+            let buckets_vaddr = bloom_vaddr
+                .checked_add(bloom_size as u64 * 8)
+                .context("DT_GNU_HASH bloom filter is out of range")?;
+            let chains_vaddr = buckets_vaddr
+                .checked_add(bucket_count as u64 * 4)
+                .context("DT_GNU_HASH buckets are out of range")?;
+
+            return Ok(Some(HashTable::Gnu(GnuHashTable {
+                bucket_count,
+                symbol_offset,
+                bloom_size,
+                bloom_shift,
+                bloom_vaddr,
+                buckets_vaddr,
+                chains_vaddr,
+            })));
+        }
+
+        if let Some(vaddr) = self.dynamic_pointer(DT_HASH) {
+            let [bucket_count, chain_count] = self.read::<[u32; 2]>(vaddr)?;
+            let buckets_vaddr = vaddr + 8;
+            let chains_vaddr = buckets_vaddr
+                .checked_add(bucket_count as u64 * 4)
+                .context("DT_HASH buckets are out of range")?;
+
+            return Ok(Some(HashTable::Sysv(SysvHashTable {
+                bucket_count,
+                chain_count,
+                buckets_vaddr,
+                chains_vaddr,
+            })));
+        }
+
+        Ok(None)
+    }
+
+    /// Number of entries in the dynamic symbol table, taken from whichever hash table exists
+    pub fn symbol_count(&self) -> Result<u64> {
+        match self
+            .hash_table()?
+            .context("Module has no symbol hash table")?
+        {
+            HashTable::Sysv(table) => Ok(table.chain_count as u64),
+            HashTable::Gnu(table) => {
+                // The highest bucket start, then walk its chain to the terminating entry
+                let buckets = self.read_array::<u32>(table.buckets_vaddr, table.bucket_count)?;
+
+                let Some(&last) = buckets.iter().max() else {
+                    return Ok(table.symbol_offset as u64);
+                };
+
+                if last < table.symbol_offset {
+                    return Ok(table.symbol_offset as u64);
+                }
+
+                // Bounded by the symbol table so a corrupt chain can't loop forever
+                for index in last as u64..self.symbol_capacity()? {
+                    let hash = self.read::<u32>(table.chain_vaddr(index)?)?;
+
+                    if hash & 1 != 0 {
+                        return Ok(index + 1);
+                    }
+                }
+
+                bail!("DT_GNU_HASH chain runs past the symbol table")
+            }
+        }
+    }
+
+    /// Reads a table of plain-old-data values with a single read
+    fn read_array<T: Pod>(&self, vaddr: u64, count: u32) -> Result<Vec<T>> {
+        let size = std::mem::size_of::<T>() as u64 * count as u64;
+
+        ensure!(
+            self.is_loaded(vaddr, size)?,
+            "Table of {} bytes at {:#x} is outside of every loaded segment",
+            size,
+            vaddr
+        );
+
+        let mut values = vec![T::zeroed(); count as usize];
+        self.data
+            .read_vaddr(vaddr, memory::bytes_of_mut(&mut values))?;
+
+        Ok(values)
+    }
+
+    pub fn raw_symbol(&self, index: u64) -> Result<RawSymbol> {
+        let symbol_table = self
+            .dynamic_pointer(DT_SYMTAB)
+            .context("Missing DT_SYMTAB")?;
+        let entry_size = self
+            .dynamic_value(DT_SYMENT)
+            .unwrap_or(std::mem::size_of::<RawSymbol>() as u64);
+
+        if entry_size != std::mem::size_of::<RawSymbol>() as u64 {
+            bail!("Unexpected symbol entry size {}", entry_size);
+        }
+
+        self.read(symbol_table + index * entry_size)
+    }
+
+    /// Upper bound on the number of symbols: the entries that fit between `DT_SYMTAB` and the end
+    /// of the segment holding it
+    fn symbol_capacity(&self) -> Result<u64> {
+        let symbol_table = self
+            .dynamic_pointer(DT_SYMTAB)
+            .context("Missing DT_SYMTAB")?;
+        let end = self
+            .load_segments()
+            .find(|ph| ph.contains(symbol_table))
+            .and_then(ProgramHeader::end)
+            .context("DT_SYMTAB is outside of every loaded segment")?;
+
+        Ok((end - symbol_table) / std::mem::size_of::<RawSymbol>() as u64)
+    }
+
+    pub fn symbol(&self, index: u64) -> Result<Symbol> {
+        let raw = self.raw_symbol(index)?;
+
+        Ok(Symbol::from_raw(&raw, self.string_at(raw.st_name)?))
+    }
+
+    /// Every entry of the dynamic symbol table, skipping the reserved null symbol. The symbol and
+    /// string tables are each pulled in with a single read.
+    pub fn symbols(&self) -> Result<Vec<Symbol>> {
+        let count = self.symbol_count()?;
+        let symbol_table = self
+            .dynamic_pointer(DT_SYMTAB)
+            .context("Missing DT_SYMTAB")?;
+        let string_table = self
+            .dynamic_pointer(DT_STRTAB)
+            .context("Missing DT_STRTAB")?;
+        let string_table_size = self.dynamic_value(DT_STRSZ).context("Missing DT_STRSZ")?;
+
+        let count = u32::try_from(count).context("Symbol count out of range")?;
+        let string_table_size =
+            u32::try_from(string_table_size).context("String table size out of range")?;
+
+        let raw_symbols = self.read_array::<RawSymbol>(symbol_table, count)?;
+        let strings = self.read_array::<u8>(string_table, string_table_size)?;
+
+        raw_symbols
+            .iter()
+            .skip(1)
+            .map(|raw| {
+                let name = strings
+                    .get(raw.st_name as usize..)
+                    .and_then(|tail| tail.iter().position(|&c| c == 0).map(|end| &tail[..end]))
+                    .with_context(|| format!("Bad symbol name offset {:#x}", raw.st_name))?;
+
+                Ok(Symbol::from_raw(
+                    raw,
+                    name.iter().map(|&c| c as char).collect(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Defined symbols with global, weak or unique binding
+    pub fn exports(&self) -> Result<Vec<Symbol>> {
+        Ok(self
+            .symbols()?
+            .into_iter()
+            .filter(|symbol| symbol.is_defined() && symbol.binding() != SymbolBinding::Local)
+            .collect())
+    }
+
+    /// Undefined symbols this module expects another module to provide
+    pub fn imports(&self) -> Result<Vec<Symbol>> {
+        Ok(self
+            .symbols()?
+            .into_iter()
+            .filter(|symbol| !symbol.is_defined() && !symbol.name.is_empty())
+            .collect())
+    }
+
+    /// Finds a defined symbol by name through the module's hash table instead of walking the
+    /// whole symbol table.
+    pub fn lookup(&self, name: &str) -> Result<Option<Symbol>> {
+        match self
+            .hash_table()?
+            .context("Module has no symbol hash table")?
+        {
+            HashTable::Gnu(table) => self.lookup_gnu(&table, name),
+            HashTable::Sysv(table) => self.lookup_sysv(&table, name),
+        }
+    }
+
+    fn lookup_gnu(&self, table: &GnuHashTable, name: &str) -> Result<Option<Symbol>> {
+        let hash = gnu_hash(name);
+
+        // Bloom filter, two bits per symbol in 64-bit words
+        if table.bloom_size > 0 {
+            let word_index = (hash / 64) % table.bloom_size;
+            let word = self.read::<u64>(table.bloom_vaddr + word_index as u64 * 8)?;
+            let mask = (1u64 << (hash % 64)) | (1u64 << ((hash >> table.bloom_shift) % 64));
+
+            if word & mask != mask {
+                return Ok(None);
+            }
+        }
+
+        let mut index =
+            self.read::<u32>(table.buckets_vaddr + (hash % table.bucket_count) as u64 * 4)? as u64;
+        let capacity = self.symbol_capacity()?;
+
+        if index < table.symbol_offset as u64 {
+            return Ok(None);
+        }
+
+        // Bounded by the symbol table so a corrupt chain can't loop forever
+        while index < capacity {
+            let chain_hash = self.read::<u32>(table.chain_vaddr(index)?)?;
+
+            if chain_hash | 1 == hash | 1 {
+                if let Some(symbol) = self.matching_symbol(index, name)? {
+                    return Ok(Some(symbol));
+                }
+            }
+
+            // The low bit marks the end of the chain
+            if chain_hash & 1 != 0 {
+                return Ok(None);
+            }
+
+            index += 1;
+        }
+
+        bail!("DT_GNU_HASH chain runs past the symbol table")
+    }
+
+    fn lookup_sysv(&self, table: &SysvHashTable, name: &str) -> Result<Option<Symbol>> {
+        if table.bucket_count == 0 {
+            return Ok(None);
+        }
+
+        let hash = sysv_hash(name);
+        let mut index =
+            self.read::<u32>(table.buckets_vaddr + (hash % table.bucket_count) as u64 * 4)?;
+
+        // Bounded by the chain length so a corrupt table can't loop forever
+        for _ in 0..table.chain_count {
+            if index == 0 {
+                break;
+            }
+
+            ensure!(
+                index < table.chain_count,
+                "Hash chain index {} is out of range",
+                index
+            );
+
+            if let Some(symbol) = self.matching_symbol(index as u64, name)? {
+                return Ok(Some(symbol));
+            }
+
+            let chain_vaddr = table
+                .chains_vaddr
+                .checked_add(index as u64 * 4)
+                .context("DT_HASH chains are out of range")?;
+            index = self.read::<u32>(chain_vaddr)?;
+        }
+
+        Ok(None)
+    }
+
+    /// The symbol at `index` if it is defined and named `name`
+    fn matching_symbol(&self, index: u64, name: &str) -> Result<Option<Symbol>> {
+        let raw = self.raw_symbol(index)?;
+
+        if raw.st_shndx == 0 {
+            return Ok(None);
+        }
+
+        let symbol_name = self.string_at(raw.st_name)?;
+
+        Ok((symbol_name == name).then(|| Symbol::from_raw(&raw, symbol_name)))
+    }
+}
+
+/// Hash function used by `DT_GNU_HASH`
+pub fn gnu_hash(name: &str) -> u32 {
+    name.bytes().fold(5381u32, |hash, c| {
+        hash.wrapping_mul(33).wrapping_add(c as u32)
+    })
+}
+
+/// Hash function used by `DT_HASH`
+pub fn sysv_hash(name: &str) -> u32 {
+    name.bytes().fold(0u32, |hash, c| {
+        let hash = (hash << 4).wrapping_add(c as u32);
+        let high = hash & 0xf000_0000;
+
+        (hash ^ (high >> 24)) & !high
+    })
+}
+
+fn align4(size: u32) -> u64 {
+    (size as u64 + 3) & !3
+}
+
+fn read_pod<T: Pod>(data: &impl ElfData, vaddr: u64) -> Result<T> {
+    let mut value = [T::zeroed()];
+    data.read_vaddr(vaddr, memory::bytes_of_mut(&mut value))?;
+
+    Ok(value[0])
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        gnu_hash, sysv_hash, Elf, ElfData, HashTable, ProgramHeader, SymbolBinding, SymbolType,
+        PT_DYNAMIC, PT_LOAD, R_X86_64_RELATIVE,
+    };
+    use crate::process::{memory, pid::Pid, process::ProcessHandle, source::MemorySource};
+    use anyhow::Result;
+
+    const LIBC: &str = "libc.so.6";
+
+    async fn self_process() -> Result<ProcessHandle> {
+        ProcessHandle::from_pid(Pid(std::process::id() as u64)).await
+    }
+
+    /// Parses the libc loaded into the test process itself
+    #[tokio::test]
+    async fn test_loaded_module() -> Result<()> {
+        let process = self_process().await?;
+        let base: u64 = process.get_module_base_address(LIBC)?.into();
+
+        let elf = Elf::from_module(&process, base)?;
+
+        assert!(elf.segment(PT_DYNAMIC).is_some());
+        assert!(elf.image_size() > 0);
+        assert!(elf.build_id()?.is_some_and(|id| id.len() >= 16));
+        assert!(elf.symbol_count()? > 100);
+
+        assert_eq!(
+            process.get_module_export(base, "getpid")?,
+            Some(libc::getpid as *const () as u64)
+        );
+        assert_eq!(process.get_module_export(base, "not_a_real_symbol")?, None);
+
+        Ok(())
+    }
+
+    /// The same module parsed out of a dump agrees with the live parse
+    #[tokio::test]
+    async fn test_dumped_module() -> Result<()> {
+        let process = self_process().await?;
+        let base: u64 = process.get_module_base_address(LIBC)?.into();
+
+        let live = Elf::from_module(&process, base)?;
+        let dump = process.dump_module(base)?;
+        let dumped = Elf::parse(dump.as_slice(), base)?;
+
+        assert_eq!(dump.len() as u64, live.image_size());
+        assert_eq!(dumped.symbol_count()?, live.symbol_count()?);
+        assert_eq!(dumped.symbol(1)?, live.symbol(1)?);
+        assert_eq!(dumped.build_id()?, live.build_id()?);
+
+        Ok(())
+    }
+
+    /// The file on disk agrees with the loaded module once translated through its segments
+    #[tokio::test]
+    async fn test_file_image() -> Result<()> {
+        let process = self_process().await?;
+        let module = process.get_module(LIBC)?;
+        let base: u64 = module.base().into();
+
+        let live = Elf::from_module(&process, base)?;
+        let file = Elf::from_file(&module.path)?;
+
+        assert_eq!(file.image_size(), live.image_size());
+        assert_eq!(file.build_id()?, live.build_id()?);
+        assert_eq!(file.symbol_count()?, live.symbol_count()?);
+
+        let getpid = file.lookup("getpid")?.expect("libc exports getpid");
+        assert_eq!(getpid.value + base, libc::getpid as *const () as u64);
+
+        let mut on_disk = [0u8; 16];
+        let mut loaded = [0u8; 16];
+        file.data().read_vaddr(getpid.value, &mut on_disk)?;
+        process.read_into(base + getpid.value, &mut loaded)?;
+        assert_eq!(on_disk, loaded);
+        assert!(file.data().file_offset(getpid.value).is_some());
+
+        // Relative relocations are packed into `DT_RELR` by newer linkers
+        let relative = file
+            .relocations()?
+            .iter()
+            .any(|relocation| relocation.relocation_type() == R_X86_64_RELATIVE);
+        assert!(relative || !file.relr_offsets()?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bounds() -> Result<()> {
+        let process = self_process().await?;
+        let base: u64 = process.get_module_base_address(LIBC)?.into();
+        let mut dump = process.dump_module(base)?;
+
+        // Header cut short
+        assert!(Elf::parse(&dump[..0x30], base).is_err());
+
+        // Symbols past the end of the table
+        let elf = Elf::parse(dump.as_slice(), base)?;
+        assert!(elf.symbol(u32::MAX as u64).is_err());
+
+        // Bad magic
+        dump[0] = 0;
+        assert!(Elf::parse(dump.as_slice(), base).is_err());
+
+        Ok(())
+    }
+
+    /// Corrupt segments and hash chains fail instead of overflowing or looping
+    #[tokio::test]
+    async fn test_corrupt_tables() -> Result<()> {
+        let process = self_process().await?;
+        let base: u64 = process.get_module_base_address(LIBC)?.into();
+        let dump = process.dump_module(base)?;
+        let elf = Elf::parse(dump.as_slice(), base)?;
+
+        // A segment that wraps around the address space
+        let segment = *elf
+            .load_segments()
+            .find(|ph| ph.p_vaddr > 0)
+            .expect("libc has more than one segment");
+        assert!(elf.read::<u64>(segment.p_vaddr).is_ok());
+
+        let mut wrapping = dump.clone();
+        let mut program_headers = elf.program_headers().to_vec();
+        for ph in program_headers
+            .iter_mut()
+            .filter(|ph| ph.p_type == PT_LOAD && ph.p_vaddr == segment.p_vaddr)
+        {
+            ph.p_memsz = u64::MAX;
+        }
+        let offset = elf.header().e_phoff as usize;
+        let bytes = memory::bytes_of_mut(&mut program_headers);
+        wrapping[offset..offset + bytes.len()].copy_from_slice(bytes);
+
+        let wrapped = Elf::parse(wrapping.as_slice(), base)?;
+        assert!(wrapped.read::<u64>(segment.p_vaddr).is_err());
+        assert!(wrapped.data().read_vaddr(u64::MAX, &mut [0u8; 8]).is_err());
+
+        // A GNU hash chain that never terminates
+        if let Some(HashTable::Gnu(table)) = elf.hash_table()? {
+            let segment_end = elf
+                .load_segments()
+                .find(|ph| ph.contains(table.chains_vaddr))
+                .and_then(ProgramHeader::end)
+                .expect("hash table is loaded");
+
+            let mut unterminated = dump.clone();
+            for vaddr in (table.chains_vaddr..segment_end).step_by(4) {
+                unterminated[vaddr as usize] &= !1;
+            }
+
+            let unterminated = Elf::parse(unterminated.as_slice(), base)?;
+            assert!(unterminated.symbol_count().is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashes() {
+        assert_eq!(gnu_hash(""), 0x0000_1505);
+        assert_eq!(gnu_hash("printf"), 0x156b_2bb8);
+        assert_eq!(sysv_hash(""), 0);
+        assert_eq!(sysv_hash("printf"), 0x0779_05a6);
+    }
+
+    /// Every export is reachable through the hash table
+    #[tokio::test]
+    async fn test_exports_and_imports() -> Result<()> {
+        let process = self_process().await?;
+        let base: u64 = process.get_module_base_address(LIBC)?.into();
+        let elf = Elf::from_module(&process, base)?;
+
+        let exports = process.get_module_exports(base)?;
+        let getpid = exports
+            .iter()
+            .find(|symbol| symbol.name == "getpid")
+            .expect("libc exports getpid");
+
+        assert_eq!(getpid.symbol_type(), SymbolType::Function);
+        assert_ne!(getpid.binding(), SymbolBinding::Local);
+
+        for export in &exports {
+            assert!(elf.lookup(&export.name)?.is_some(), "{}", export.name);
+        }
+
+        let imports = process.get_module_imports(base)?;
+        assert!(imports.iter().all(|symbol| !symbol.is_defined()));
+        assert!(!imports.is_empty());
+
+        Ok(())
+    }
+}
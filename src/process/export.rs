@@ -0,0 +1,254 @@
+use std::{fmt::Write, path::Path, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use super::{
+    netvars::NetVar,
+    offsets::{Offsets, NETVARS},
+};
+
+/// Formats `Offsets` can be written out as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The serde form of `Offsets`, which `Offsets::from_json` loads back
+    Json,
+    /// A module of nested `pub mod`s holding `pub const`s
+    Rust,
+    /// A header of `static const uintptr_t`s, named after their path in the tree
+    CHeader,
+}
+
+impl ExportFormat {
+    /// Picks the format from a file extension: `.json`, `.rs`, `.h` or `.hpp`
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .with_context(|| format!("'{}' has no extension", path.display()))?
+            .parse()
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        Ok(match format.to_ascii_lowercase().as_str() {
+            "json" => ExportFormat::Json,
+            "rs" | "rust" => ExportFormat::Rust,
+            "h" | "hpp" | "c" => ExportFormat::CHeader,
+            _ => bail!("Unknown export format '{}'", format),
+        })
+    }
+}
+
+const GENERATED_NOTICE: &str = "Generated by make_it_fair, do not edit";
+
+impl Offsets {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Unable to parse offsets")
+    }
+
+    /// Renders the whole tree, library bases, interfaces, direct addresses and every netvar
+    pub fn export(&self, format: ExportFormat) -> Result<String> {
+        if format == ExportFormat::Json {
+            return self.to_json();
+        }
+
+        let Value::Object(tree) = serde_json::to_value(self)? else {
+            bail!("Offsets did not serialize to an object");
+        };
+
+        let mut output = String::new();
+
+        match format {
+            ExportFormat::Rust => {
+                writeln!(output, "// {}", GENERATED_NOTICE)?;
+                writeln!(output, "#![allow(non_upper_case_globals, dead_code)]")?;
+
+                for (name, value) in &tree {
+                    write_rust(&mut output, name, name, value, 0)?;
+                }
+            }
+            ExportFormat::CHeader => {
+                writeln!(output, "// {}", GENERATED_NOTICE)?;
+                writeln!(output, "#pragma once\n\n#include <stdint.h>")?;
+
+                for (name, value) in &tree {
+                    write_c(&mut output, name, value)?;
+                }
+            }
+            ExportFormat::Json => unreachable!(),
+        }
+
+        Ok(output)
+    }
+
+    /// Writes the offsets to `path`, in the format its extension names
+    pub fn export_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let output = self.export(ExportFormat::from_path(path)?)?;
+
+        std::fs::write(path, output)
+            .with_context(|| format!("Unable to write offsets to '{}'", path.display()))
+    }
+}
+
+fn write_rust(
+    output: &mut String,
+    path: &str,
+    name: &str,
+    value: &Value,
+    depth: usize,
+) -> Result<()> {
+    let indent = "    ".repeat(depth);
+
+    match value {
+        Value::Object(children) => {
+            writeln!(output, "\n{}pub mod {} {{", indent, name)?;
+
+            for (child, value) in children {
+                let child_path = format!("{}.{}", path, child);
+                write_rust(output, &child_path, child, value, depth + 1)?;
+            }
+
+            writeln!(output, "{}}}", indent)?;
+        }
+        value => {
+            if let Some(netvar) = netvar(path) {
+                writeln!(
+                    output,
+                    "{}/// `{}::{}`, {}",
+                    indent, netvar.class, netvar.field, netvar.value_type
+                )?;
+            }
+
+            writeln!(
+                output,
+                "{}pub const {}: usize = {:#x};",
+                indent,
+                name,
+                leaf(path, value)?
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_c(output: &mut String, path: &str, value: &Value) -> Result<()> {
+    match value {
+        Value::Object(children) => {
+            writeln!(output, "\n// {}", path.replace('.', "::"))?;
+
+            for (child, value) in children {
+                write_c(output, &format!("{}.{}", path, child), value)?;
+            }
+        }
+        value => {
+            let comment = netvar(path)
+                .map(|netvar| {
+                    format!(
+                        " // {}::{} ({})",
+                        netvar.class,
+                        netvar.field,
+                        netvar.value_type.c_type()
+                    )
+                })
+                .unwrap_or_default();
+
+            writeln!(
+                output,
+                "static const uintptr_t {} = {:#x};{}",
+                path.replace('.', "_"),
+                leaf(path, value)?,
+                comment
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The declaration behind a leaf of the tree, `None` outside of `network`
+fn netvar(path: &str) -> Option<&'static NetVar> {
+    NETVARS.iter().find(|netvar| netvar.path() == path)
+}
+
+fn leaf(name: &str, value: &Value) -> Result<u64> {
+    value
+        .as_u64()
+        .with_context(|| format!("Offset '{}' is not an integer: {}", name, value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExportFormat;
+    use crate::process::{memory::Address, offsets::Offsets};
+    use anyhow::Result;
+
+    fn offsets() -> Offsets {
+        let mut offsets = Offsets::default();
+        offsets.library.client = Address::from(0x7f00_0000_0000);
+        offsets.direct.local_controller = Address::from(0x7f00_0012_3450);
+        offsets.network.pawn.m_iHealth = Address::from(0x344);
+        offsets.network.item_service.m_bHasHelmet = Address::from(0x41);
+
+        offsets
+    }
+
+    #[test]
+    fn test_json_round_trip() -> Result<()> {
+        let offsets = offsets();
+        let loaded = Offsets::from_json(&offsets.export(ExportFormat::Json)?)?;
+
+        assert_eq!(loaded.to_json()?, offsets.to_json()?);
+        assert_eq!(loaded.network.pawn.m_iHealth, Address::from(0x344));
+
+        assert!(Offsets::from_json("{}").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rust_and_c() -> Result<()> {
+        let offsets = offsets();
+
+        let rust = offsets.export(ExportFormat::Rust)?;
+        assert!(rust.contains("pub mod network {"));
+        assert!(rust.contains("        pub const m_iHealth: usize = 0x344;"));
+        assert!(rust.contains("        /// `C_CSPlayerPawn::m_iHealth`, I32\n"));
+        assert!(rust.contains("    pub const local_controller: usize = 0x7f0000123450;"));
+        assert_eq!(rust.matches('{').count(), rust.matches('}').count());
+
+        let header = offsets.export(ExportFormat::CHeader)?;
+        assert!(header.contains("#pragma once"));
+        assert!(header.contains(
+            "static const uintptr_t network_pawn_m_iHealth = 0x344; // C_CSPlayerPawn::m_iHealth (int32_t)"
+        ));
+        assert!(header.contains("static const uintptr_t library_client = 0x7f0000000000;\n"));
+        assert!(header.contains("static const uintptr_t network_item_service_m_bHasHelmet = 0x41;"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_from_path() -> Result<()> {
+        assert_eq!(ExportFormat::from_path("offsets.json")?, ExportFormat::Json);
+        assert_eq!(
+            ExportFormat::from_path("out/offsets.rs")?,
+            ExportFormat::Rust
+        );
+        assert_eq!(ExportFormat::from_path("offsets.h")?, ExportFormat::CHeader);
+        assert!(ExportFormat::from_path("offsets").is_err());
+        assert!(ExportFormat::from_path("offsets.txt").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+use std::{path::Path, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+
+use super::memory::Address;
+
+/// Access flags of a mapping, the `rwxp` column of `/proc/<pid>/maps`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+    pub shared: bool,
+}
+
+impl FromStr for Permissions {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let flags = value.as_bytes();
+
+        if flags.len() != 4 {
+            bail!("Malformed permissions '{}'", value);
+        }
+
+        Ok(Self {
+            read: flags[0] == b'r',
+            write: flags[1] == b'w',
+            execute: flags[2] == b'x',
+            shared: flags[3] == b's',
+        })
+    }
+}
+
+/// A single line of `/proc/<pid>/maps`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub end: u64,
+    pub permissions: Permissions,
+    pub offset: u64,
+    pub device: String,
+    pub inode: u64,
+    pub path: Option<String>,
+}
+
+impl MemoryRegion {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn contains(&self, address: impl Into<u64>) -> bool {
+        let address = address.into();
+        address >= self.start && address < self.end
+    }
+
+    /// File name of the backing path, `None` for anonymous and pseudo (`[heap]`) mappings
+    pub fn file_name(&self) -> Option<&str> {
+        self.path
+            .as_deref()
+            .filter(|path| path.starts_with('/'))
+            .and_then(|path| Path::new(path).file_name())
+            .and_then(|name| name.to_str())
+    }
+}
+
+impl FromStr for MemoryRegion {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let mut fields = line.splitn(6, ' ');
+        let mut next = |name: &str| {
+            fields
+                .next()
+                .filter(|field| !field.is_empty())
+                .with_context(|| format!("Maps line is missing {}: '{}'", name, line))
+        };
+
+        let (start, end) = next("range")?
+            .split_once('-')
+            .context("Malformed address range")?;
+        let permissions = next("permissions")?.parse()?;
+        let offset = next("offset")?;
+        let device = next("device")?.to_string();
+        let inode = next("inode")?;
+        let path = fields
+            .next()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string);
+
+        Ok(Self {
+            start: u64::from_str_radix(start, 16).context("Unable to parse start address")?,
+            end: u64::from_str_radix(end, 16).context("Unable to parse end address")?,
+            permissions,
+            offset: u64::from_str_radix(offset, 16).context("Unable to parse offset")?,
+            device,
+            inode: inode.parse().context("Unable to parse inode")?,
+            path,
+        })
+    }
+}
+
+/// Every mapping of one file, grouped out of a `MemoryMap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub name: String,
+    pub path: String,
+    pub regions: Vec<MemoryRegion>,
+}
+
+impl Module {
+    /// Start of the lowest mapping, where the ELF header lives
+    pub fn base(&self) -> Address {
+        self.regions
+            .iter()
+            .map(|region| region.start)
+            .min()
+            .unwrap_or_default()
+            .into()
+    }
+
+    pub fn end(&self) -> u64 {
+        self.regions
+            .iter()
+            .map(|region| region.end)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Span from the base to the end of the last mapping, holes included
+    pub fn size(&self) -> u64 {
+        self.end() - u64::from(self.base())
+    }
+
+    pub fn contains(&self, address: impl Into<u64>) -> bool {
+        let address = address.into();
+        self.regions.iter().any(|region| region.contains(address))
+    }
+
+    pub fn executable_regions(&self) -> impl Iterator<Item = &MemoryRegion> {
+        self.regions
+            .iter()
+            .filter(|region| region.permissions.execute)
+    }
+}
+
+/// A parsed `/proc/<pid>/maps` listing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryMap {
+    regions: Vec<MemoryRegion>,
+}
+
+impl MemoryMap {
+    pub fn new(regions: Vec<MemoryRegion>) -> Self {
+        Self { regions }
+    }
+
+    pub fn parse(maps: &str) -> Result<Self> {
+        let regions = maps
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<MemoryRegion>>>()?;
+
+        Ok(Self { regions })
+    }
+
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.regions
+    }
+
+    pub fn region_containing(&self, address: impl Into<u64>) -> Option<&MemoryRegion> {
+        let address = address.into();
+        self.regions.iter().find(|region| region.contains(address))
+    }
+
+    /// Every file backed module, in the order they first appear
+    pub fn modules(&self) -> Vec<Module> {
+        let mut modules: Vec<Module> = vec![];
+
+        for region in &self.regions {
+            let (Some(name), Some(path)) = (region.file_name(), region.path.as_deref()) else {
+                continue;
+            };
+
+            match modules.iter_mut().find(|module| module.path == path) {
+                Some(module) => module.regions.push(region.clone()),
+                None => modules.push(Module {
+                    name: name.to_string(),
+                    path: path.to_string(),
+                    regions: vec![region.clone()],
+                }),
+            }
+        }
+
+        modules
+    }
+
+    /// Finds a module by its exact file name, e.g. `libclient.so`
+    pub fn module(&self, name: &str) -> Option<Module> {
+        self.modules()
+            .into_iter()
+            .find(|module| module.name == name)
+    }
+
+    pub fn module_containing(&self, address: impl Into<u64>) -> Option<Module> {
+        let address = address.into();
+        self.modules()
+            .into_iter()
+            .find(|module| module.contains(address))
+    }
+}
+
+impl FromStr for MemoryMap {
+    type Err = anyhow::Error;
+
+    fn from_str(maps: &str) -> Result<Self> {
+        Self::parse(maps)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryMap;
+    use crate::process::memory::Address;
+    use anyhow::Result;
+
+    const MAPS: &str = "\
+7f0000000000-7f0000001000 r--p 00000000 fd:01 100 /game/bin/linuxsteamrt64/libclient.so
+7f0000001000-7f0000005000 r-xp 00001000 fd:01 100 /game/bin/linuxsteamrt64/libclient.so
+7f0000005000-7f0000006000 rw-p 00005000 fd:01 100 /game/bin/linuxsteamrt64/libclient.so
+7f0000006000-7f0000007000 rw-p 00000000 00:00 0
+7f0000010000-7f0000012000 r-xp 00000000 fd:01 200 /tmp/not_libclient.so.bak
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0                          [stack]
+7f0000020000-7f0000021000 r--p 00000000 fd:01 300 /home/user/My Games/libtier0.so
+";
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let map = MemoryMap::parse(MAPS)?;
+
+        assert_eq!(map.regions().len(), 7);
+
+        let region = &map.regions()[1];
+        assert_eq!(region.start, 0x7f0000001000);
+        assert_eq!(region.size(), 0x4000);
+        assert!(region.permissions.read && region.permissions.execute);
+        assert!(!region.permissions.write && !region.permissions.shared);
+        assert_eq!(region.offset, 0x1000);
+        assert_eq!(region.inode, 100);
+
+        assert_eq!(map.regions()[3].path, None);
+        assert_eq!(map.regions()[5].path.as_deref(), Some("[stack]"));
+        assert_eq!(map.regions()[5].file_name(), None);
+        assert_eq!(
+            map.regions()[6].path.as_deref(),
+            Some("/home/user/My Games/libtier0.so")
+        );
+
+        assert!(MemoryMap::parse("not a maps line").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_modules() -> Result<()> {
+        let map = MemoryMap::parse(MAPS)?;
+
+        let client = map.module("libclient.so").expect("libclient.so is mapped");
+        assert_eq!(client.base(), Address::from(0x7f0000000000));
+        assert_eq!(client.regions.len(), 3);
+        assert_eq!(client.size(), 0x6000);
+
+        let executable: Vec<_> = client.executable_regions().collect();
+        assert_eq!(executable.len(), 1);
+        assert_eq!(executable[0].start, 0x7f0000001000);
+
+        // Substrings of other paths don't match
+        assert_eq!(map.module("client.so"), None);
+        assert_eq!(map.modules().len(), 3);
+
+        assert_eq!(
+            map.module_containing(0x7f0000002000u64).map(|m| m.name),
+            Some("libclient.so".to_string())
+        );
+        assert_eq!(map.module_containing(0x7f0000006000u64), None);
+        assert!(map.region_containing(0x7f0000006000u64).is_some());
+        assert!(map.region_containing(0x1000u64).is_none());
+
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+use serde::{Deserialize, Serialize};
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Address(u64);
 
 impl Address {
@@ -55,47 +58,60 @@ impl Default for Address {
     }
 }
 
-pub fn check_elf_header(data: &[u8]) -> bool {
-    data.len() >= 4 && data[0..4] == [0x7f, b'E', b'L', b'F']
+/// Marker for `#[repr(C)]` plain-old-data types that can be decoded straight from remote memory.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` (or `#[repr(transparent)]`), contain no pointers, references
+/// or padding, and be valid for every possible bit pattern.
+pub unsafe trait Pod: Copy + 'static {
+    fn zeroed() -> Self {
+        // SAFETY: all-zero bytes are a valid bit pattern for any `Pod`
+        unsafe { std::mem::zeroed() }
+    }
 }
 
-pub fn read_u64_vec(data: &[u8], address: u64) -> u64 {
-    let adr = address as usize;
-    let buffer = [
-        data[adr],
-        data[adr + 1],
-        data[adr + 2],
-        data[adr + 3],
-        data[adr + 4],
-        data[adr + 5],
-        data[adr + 6],
-        data[adr + 7],
-    ];
-
-    u64::from_ne_bytes(buffer)
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
 }
 
-pub fn read_string_vec(data: &[u8], address: u64) -> String {
-    let mut string = String::new();
-    let mut i = address;
-    loop {
-        let c = data[i as usize];
-        if c == 0 {
-            break;
-        }
-        string.push(c as char);
-        i += 1;
+impl_pod!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64, Address);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Views a slice of plain-old-data values as its raw bytes.
+pub fn bytes_of_mut<T: Pod>(values: &mut [T]) -> &mut [u8] {
+    // SAFETY: `Pod` types have no padding and accept any bit pattern, so every byte is
+    // initialized and any bytes written back are a valid `T`
+    unsafe {
+        std::slice::from_raw_parts_mut(
+            values.as_mut_ptr().cast::<u8>(),
+            std::mem::size_of_val(values),
+        )
     }
-    string
 }
 
-#[allow(unused)]
-pub fn read_u32_vec(data: &[u8], address: u64) -> Address {
-    let adr = address as usize;
-    let buffer = [data[adr], data[adr + 1], data[adr + 2], data[adr + 3]];
-    let val: u64 = u32::from_ne_bytes(buffer) as u64;
+/// Decodes a `T` at `offset` into a buffer, such as a module dump. `None` when it would run past
+/// the end.
+pub fn read_pod_at<T: Pod>(data: &[u8], offset: u64) -> Option<T> {
+    let start = usize::try_from(offset).ok()?;
+    let bytes = data.get(start..start.checked_add(std::mem::size_of::<T>())?)?;
+
+    let mut value = [T::zeroed()];
+    bytes_of_mut(&mut value).copy_from_slice(bytes);
 
-    val.into()
+    Some(value[0])
+}
+
+/// Reads the NUL terminated string at `offset` into a buffer. `None` when it isn't terminated
+/// before the end.
+pub fn read_str_at(data: &[u8], offset: u64) -> Option<String> {
+    let tail = data.get(usize::try_from(offset).ok()?..)?;
+    let end = memchr::memchr(0, tail)?;
+
+    Some(tail[..end].iter().map(|&c| c as char).collect())
 }
 
 macro_rules! impl_for_address {
@@ -130,3 +146,31 @@ impl_for_address!(BitOr, bitor);
 impl_for_address!(BitXor, bitxor);
 impl_for_address!(Shl, shl, u32);
 impl_for_address!(Shr, shr, u32);
+
+#[cfg(test)]
+mod test {
+    use super::{read_pod_at, read_str_at, Address};
+
+    #[test]
+    fn test_buffer_reads() {
+        let mut data = vec![0u8; 0x10];
+        data[0x00..0x08].copy_from_slice(&0x1020u64.to_le_bytes());
+        data[0x08..0x0C].copy_from_slice(b"cs2\0");
+        data[0x0C..0x10].copy_from_slice(b"tail");
+
+        assert_eq!(
+            read_pod_at::<Address>(&data, 0),
+            Some(Address::from(0x1020))
+        );
+        assert_eq!(
+            read_pod_at::<u32>(&data, 0x0C),
+            Some(u32::from_le_bytes(*b"tail"))
+        );
+        assert_eq!(read_pod_at::<u64>(&data, 0x0C), None);
+        assert_eq!(read_pod_at::<u64>(&data, u64::MAX), None);
+
+        assert_eq!(read_str_at(&data, 0x08).as_deref(), Some("cs2"));
+        assert_eq!(read_str_at(&data, 0x0C), None);
+        assert_eq!(read_str_at(&data, 0x20), None);
+    }
+}
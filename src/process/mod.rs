@@ -1,4 +1,19 @@
+pub mod elf;
+pub mod export;
+pub mod maps;
 pub mod memory;
+pub mod netvars;
+pub mod offline;
+pub mod offset_cache;
+pub mod offset_report;
 pub mod offsets;
+pub mod overrides;
 pub mod pid;
+#[allow(clippy::module_inception)]
 pub mod process;
+pub mod remote_ptr;
+pub mod schema;
+pub mod signature;
+pub mod snapshot;
+pub mod source;
+pub mod strategies;
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use super::{memory::Address, offsets::NetVarOffsets};
+
+/// What a netvar holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetVarType {
+    Bool,
+    I8,
+    U8,
+    U16,
+    I32,
+    U32,
+    U64,
+    F32,
+    Vec3,
+    /// Pointer to a NUL terminated string
+    String,
+    /// `CHandle`, an entity index and serial packed into a `u32`
+    Handle,
+    /// `CUtlVector<CHandle>`, a count followed by a pointer to the handles
+    HandleVector,
+    /// Pointer to another object, such as a services component
+    Pointer,
+    /// Another object stored inline, its own fields are relative to where it starts
+    Embedded,
+}
+
+impl NetVarType {
+    /// Closest C type, for exported headers
+    pub fn c_type(&self) -> &'static str {
+        match self {
+            NetVarType::Bool => "bool",
+            NetVarType::I8 => "int8_t",
+            NetVarType::U8 => "uint8_t",
+            NetVarType::U16 => "uint16_t",
+            NetVarType::I32 => "int32_t",
+            NetVarType::U32 => "uint32_t",
+            NetVarType::U64 => "uint64_t",
+            NetVarType::F32 => "float",
+            NetVarType::Vec3 => "float[3]",
+            NetVarType::String => "const char*",
+            NetVarType::Handle => "uint32_t",
+            NetVarType::HandleVector => "CUtlVector<uint32_t>",
+            NetVarType::Pointer => "void*",
+            NetVarType::Embedded => "uint8_t[]",
+        }
+    }
+}
+
+impl Display for NetVarType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// Nothing useful can be read without it, resolution fails when it is missing
+    Required,
+    /// Its `Player` fields are left empty when it is missing
+    Optional,
+}
+
+/// Which names the heuristic dump scan accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanMarker {
+    /// Only names preceded by an `MNetworkEnable` marker
+    Networked,
+    /// Any name
+    Any,
+}
+
+/// Where the heuristic dump scan finds the value, relative to the name pointer it matched on
+#[derive(Debug, Clone, Copy)]
+pub struct ScanRule {
+    pub marker: ScanMarker,
+    pub offset: u64,
+}
+
+/// One netvar as declared in `netvars!`, with accessors into `NetVarOffsets`.
+pub struct NetVar {
+    /// Field of `NetVarOffsets` holding the group, e.g. `pawn`
+    pub group: &'static str,
+    /// Field of the group struct, e.g. `m_iHealth`
+    pub name: &'static str,
+    /// Schema class the field is looked up in, fields of base classes are found through it
+    pub class: &'static str,
+    /// Name of the field in the schema and in the client's strings
+    pub field: &'static str,
+    pub requirement: Requirement,
+    pub value_type: NetVarType,
+    pub scan: ScanRule,
+    pub get: fn(&NetVarOffsets) -> Address,
+    pub get_mut: fn(&mut NetVarOffsets) -> &mut Address,
+}
+
+impl NetVar {
+    /// Path inside of `Offsets`, e.g. `network.pawn.m_iHealth`
+    pub fn path(&self) -> String {
+        format!("network.{}.{}", self.group, self.name)
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.requirement == Requirement::Required
+    }
+}
+
+/// Declares every netvar once. Generates `NetVarOffsets`, one struct per group, and the
+/// `NETVARS` table that resolution, validation and export walk.
+///
+/// ```ignore
+/// netvars! {
+///     pawn: PawnOffsets {
+///         m_iHealth: I32 = ("C_CSPlayerPawn", "m_iHealth"), Required, scan(Networked, 0x18);
+///     }
+/// }
+/// ```
+macro_rules! netvars {
+    (
+        $(
+            $group:ident: $group_type:ident {
+                $(
+                    $name:ident: $value_type:ident = ($class:literal, $field:literal),
+                    $requirement:ident, scan($marker:ident, $scan_offset:literal);
+                )*
+            }
+        )*
+    ) => {
+        #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+        pub struct NetVarOffsets {
+            $(pub $group: $group_type,)*
+        }
+
+        $(
+            #[allow(non_snake_case)]
+            #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+            pub struct $group_type {
+                $(pub $name: Address,)*
+            }
+        )*
+
+        /// Every netvar, in declaration order
+        pub static NETVARS: &[$crate::process::netvars::NetVar] = &[
+            $($(
+                $crate::process::netvars::NetVar {
+                    group: stringify!($group),
+                    name: stringify!($name),
+                    class: $class,
+                    field: $field,
+                    requirement: $crate::process::netvars::Requirement::$requirement,
+                    value_type: $crate::process::netvars::NetVarType::$value_type,
+                    scan: $crate::process::netvars::ScanRule {
+                        marker: $crate::process::netvars::ScanMarker::$marker,
+                        offset: $scan_offset,
+                    },
+                    get: |offsets| offsets.$group.$name,
+                    get_mut: |offsets| &mut offsets.$group.$name,
+                },
+            )*)*
+        ];
+    };
+}
+
+pub(crate) use netvars;
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::process::{memory::Address, offsets::NetVarOffsets, offsets::NETVARS};
+
+    #[test]
+    fn test_table() {
+        let paths: HashSet<String> = NETVARS.iter().map(|netvar| netvar.path()).collect();
+        assert_eq!(paths.len(), NETVARS.len(), "Netvars are declared once");
+
+        let health = NETVARS
+            .iter()
+            .find(|netvar| netvar.path() == "network.pawn.m_iHealth")
+            .expect("m_iHealth is declared");
+        assert_eq!(health.class, "C_CSPlayerPawn");
+        assert!(health.is_required());
+
+        // The accessors point at the struct field of the same name
+        let mut offsets = NetVarOffsets::default();
+        *(health.get_mut)(&mut offsets) = Address::from(0x344);
+        assert_eq!(offsets.pawn.m_iHealth, Address::from(0x344));
+        assert_eq!((health.get)(&offsets), Address::from(0x344));
+    }
+}
@@ -0,0 +1,295 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+
+use super::{
+    elf::{Elf, ElfData, R_X86_64_RELATIVE},
+    maps::{MemoryMap, MemoryRegion, Permissions},
+    source::MemorySource,
+};
+use crate::constant::{CLIENT_LIB, ENGINE_LIB, SCHEMA_LIB, TIER0_LIB};
+
+/// Where the first image is placed, each following one goes `IMAGE_SPACING` higher
+const FIRST_IMAGE_BASE: u64 = 0x7f00_0000_0000;
+const IMAGE_SPACING: u64 = 0x1_0000_0000;
+
+const PAGE_SIZE: u64 = 0x1000;
+
+/// Upper bound on directories walked while looking for a library in an install
+const MAX_SEARCH_DEPTH: usize = 8;
+
+/// One library laid out the way the loader would map it
+struct OfflineImage {
+    path: String,
+    base: u64,
+    /// Indexed by virtual address, relative relocations applied
+    image: Vec<u8>,
+    regions: Vec<MemoryRegion>,
+}
+
+/// Libraries read straight from disk, mapped at made up bases, so offsets can be resolved
+/// without a running game.
+///
+/// Only what the file holds is there: relative relocations are applied, but nothing the game
+/// allocates at runtime (interfaces, the schema system's classes, entities) exists.
+pub struct OfflineSource {
+    images: Vec<OfflineImage>,
+}
+
+impl OfflineSource {
+    /// Maps each library in turn
+    pub fn open(paths: impl IntoIterator<Item = impl AsRef<Path>>) -> Result<Self> {
+        let mut images = vec![];
+
+        for (i, path) in paths.into_iter().enumerate() {
+            let base = FIRST_IMAGE_BASE + i as u64 * IMAGE_SPACING;
+            images.push(OfflineImage::load(path.as_ref(), base)?);
+        }
+
+        Ok(Self { images })
+    }
+
+    /// Finds the client, engine and tier0 libraries under a CS2 install directory, plus the
+    /// schema system when it is there
+    pub fn from_install(install: impl AsRef<Path>) -> Result<Self> {
+        let install = install.as_ref();
+        let mut paths = vec![];
+
+        for library in [CLIENT_LIB, ENGINE_LIB, TIER0_LIB] {
+            paths.push(find_library(install, library).with_context(|| {
+                format!("Unable to find {} under '{}'", library, install.display())
+            })?);
+        }
+
+        match find_library(install, SCHEMA_LIB) {
+            Some(path) => paths.push(path),
+            None => warn!("No {} under '{}'", SCHEMA_LIB, install.display()),
+        }
+
+        Self::open(paths)
+    }
+}
+
+impl OfflineImage {
+    fn load(path: &Path, base: u64) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Unable to find '{}'", path.display()))?;
+        let elf = Elf::from_file(&path)?;
+
+        let mut image = vec![0u8; elf.image_size() as usize];
+        let mut regions = vec![];
+
+        for segment in elf.load_segments() {
+            let start = segment.p_vaddr as usize;
+            let end = start + segment.p_memsz as usize;
+            elf.data()
+                .read_vaddr(segment.p_vaddr, &mut image[start..end])?;
+
+            regions.push(MemoryRegion {
+                start: base + segment.p_vaddr / PAGE_SIZE * PAGE_SIZE,
+                end: base + (segment.p_vaddr + segment.p_memsz).div_ceil(PAGE_SIZE) * PAGE_SIZE,
+                permissions: Permissions {
+                    read: segment.is_readable(),
+                    write: segment.is_writable(),
+                    execute: segment.is_executable(),
+                    shared: false,
+                },
+                offset: segment.p_offset / PAGE_SIZE * PAGE_SIZE,
+                device: "00:00".to_string(),
+                inode: 0,
+                path: Some(path.to_string_lossy().into_owned()),
+            });
+        }
+
+        // Pointers into the image itself, everything else needs symbols from other libraries
+        let mut relocated = 0;
+        for relocation in elf.relocations()? {
+            if relocation.relocation_type() != R_X86_64_RELATIVE {
+                continue;
+            }
+
+            let slot = slot(&mut image, relocation.r_offset, &path)?;
+            slot.copy_from_slice(&base.wrapping_add_signed(relocation.r_addend).to_le_bytes());
+            relocated += 1;
+        }
+
+        // Packed relative relocations keep their addend in the slot
+        for offset in elf.relr_offsets()? {
+            let slot = slot(&mut image, offset, &path)?;
+            let value = u64::from_le_bytes((&*slot).try_into()?);
+            slot.copy_from_slice(&base.wrapping_add(value).to_le_bytes());
+            relocated += 1;
+        }
+
+        debug!(
+            "Mapped '{}' at {:#x}, {} relocations applied",
+            path.display(),
+            base,
+            relocated
+        );
+
+        Ok(Self {
+            path: path.to_string_lossy().into_owned(),
+            base,
+            image,
+            regions,
+        })
+    }
+}
+
+impl MemorySource for OfflineSource {
+    fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+        let image = self
+            .images
+            .iter()
+            .find(|image| address >= image.base && address < image.base + image.image.len() as u64)
+            .with_context(|| format!("{:#x} is outside of every image", address))?;
+
+        let start = (address - image.base) as usize;
+        let bytes = image
+            .image
+            .get(start..start + buffer.len())
+            .with_context(|| {
+                format!(
+                    "Read of {} bytes at {:#x} runs past the end of '{}'",
+                    buffer.len(),
+                    address,
+                    image.path
+                )
+            })?;
+
+        buffer.copy_from_slice(bytes);
+
+        Ok(())
+    }
+
+    fn memory_map(&self) -> Result<MemoryMap> {
+        Ok(MemoryMap::new(
+            self.images
+                .iter()
+                .flat_map(|image| image.regions.iter().cloned())
+                .collect(),
+        ))
+    }
+}
+
+/// The pointer sized slot a relocation writes to
+fn slot<'a>(image: &'a mut [u8], offset: u64, path: &Path) -> Result<&'a mut [u8]> {
+    image
+        .get_mut(offset as usize..)
+        .and_then(|slot| slot.get_mut(..8))
+        .with_context(|| {
+            format!(
+                "Relocation at {:#x} is outside of '{}'",
+                offset,
+                path.display()
+            )
+        })
+}
+
+/// First file called `name` under `directory`, breadth first so the shallowest one wins
+fn find_library(directory: &Path, name: &str) -> Option<PathBuf> {
+    let mut level = vec![directory.to_path_buf()];
+
+    for _ in 0..MAX_SEARCH_DEPTH {
+        let mut next = vec![];
+
+        for directory in &level {
+            let Ok(entries) = std::fs::read_dir(directory) else {
+                continue;
+            };
+
+            let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+            entries.sort_by_key(|entry| entry.file_name());
+
+            for entry in entries {
+                let path = entry.path();
+
+                if path.is_dir() {
+                    next.push(path);
+                } else if entry.file_name() == name {
+                    return Some(path);
+                }
+            }
+        }
+
+        if next.is_empty() {
+            break;
+        }
+
+        level = next;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::OfflineSource;
+    use crate::process::{
+        elf::Elf, pid::Pid, process::ProcessHandle, signature::Signature, source::MemorySource,
+    };
+    use anyhow::Result;
+
+    const LIBC: &str = "libc.so.6";
+
+    /// Resolving against libc on disk lands on the same relative addresses as the loaded libc
+    #[tokio::test]
+    async fn test_matches_loaded_module() -> Result<()> {
+        let process = ProcessHandle::from_pid(Pid(std::process::id() as u64)).await?;
+        let live = process.get_module(LIBC)?;
+        let live_base: u64 = live.base().into();
+
+        let offline = OfflineSource::open([&live.path])?;
+        let module = offline.get_module(LIBC)?;
+        let base: u64 = module.base().into();
+
+        // The live module's `.bss` tail is an anonymous mapping, so compare the images instead
+        assert_eq!(offline.module_size(base)?, process.module_size(live_base)?);
+
+        let getpid = offline
+            .get_module_export(base, "getpid")?
+            .expect("libc exports getpid");
+        assert_eq!(getpid - base, libc::getpid as *const () as u64 - live_base);
+
+        // The start of getpid is unique enough to scan for
+        let mut code = [0u8; 24];
+        process.read_into(libc::getpid as *const () as u64, &mut code)?;
+        let signature = Signature::parse(
+            &code
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<_>>()
+                .join(" "),
+        )?;
+        let found = offline.scan_signature(&module, &signature)?;
+        assert!(found.contains(&getpid));
+
+        // Pointers are relocated the way the loader relocated the live ones
+        let elf = Elf::from_file(&live.path)?;
+        let relocated = match elf.relr_offsets()?.first() {
+            Some(offset) => *offset,
+            None => {
+                elf.relocations()?
+                    .first()
+                    .expect("libc is relocated")
+                    .r_offset
+            }
+        };
+        assert_eq!(
+            offline.read::<u64>(base + relocated)? - base,
+            process.read::<u64>(live_base + relocated)? - live_base
+        );
+
+        assert!(offline.read::<u8>(base + module.size() + 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_install() {
+        assert!(OfflineSource::from_install("/nonexistent/cs2").is_err());
+    }
+}
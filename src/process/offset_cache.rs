@@ -0,0 +1,291 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{ensure, Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    elf::{Elf, ElfData},
+    memory::Address,
+    offset_report::ResolutionMethod,
+    offsets::{NetVarOffsets, Offsets},
+    overrides::Overrides,
+    source::MemorySource,
+    strategies::resolve_direct_offsets,
+};
+use crate::constant::CLIENT_LIB;
+
+/// Overrides where the cache lives, an empty value turns caching off
+pub const CACHE_PATH_VAR: &str = "MAKE_IT_FAIR_OFFSET_CACHE";
+
+/// Bumped whenever the layout of `CachedOffsets` or the way offsets are found changes
+const CACHE_VERSION: u32 = 3;
+
+/// The part of `Offsets` that only changes when the game updates. Addresses are kept relative to
+/// the module they live in, so the entry stays good across launches. Netvars are only cached when
+/// the schema resolved them, see `OffsetCache::store`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedOffsets {
+    version: u32,
+    /// Build the offsets were resolved against, see `build_key`
+    client_build: String,
+    /// `DirectOffsets::local_controller`, relative to the base of `libclient.so`
+    local_controller: u64,
+    network: NetVarOffsets,
+}
+
+/// Resolved offsets persisted to disk, so a launch against an unchanged build can skip the scans.
+pub struct OffsetCache {
+    path: PathBuf,
+}
+
+impl OffsetCache {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The cache at `$MAKE_IT_FAIR_OFFSET_CACHE`, or `make_it_fair/offsets.json` in the XDG cache
+    /// directory. `None` if caching was turned off or there is nowhere to put it.
+    pub fn from_env() -> Option<Self> {
+        if let Some(path) = std::env::var_os(CACHE_PATH_VAR) {
+            return (!path.is_empty()).then(|| Self::new(path));
+        }
+
+        let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+
+        Some(Self::new(
+            cache_dir.join("make_it_fair").join("offsets.json"),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Offsets from the cache if they were resolved against the running build, otherwise a full
+    /// scan whose result is written back. A cache that can't be read or written only costs the
+    /// scan.
+    pub fn load_or_find(&self, process: &impl MemorySource) -> Result<Offsets> {
+        let mut offsets = Offsets::default();
+        offsets
+            .library
+            .set_offsets(process)
+            .context("Unable to set Library Offsets")?;
+
+        let build = build_key(process, offsets.library.client.into())
+            .context("Unable to identify the client build")?;
+
+        match self.load(&build) {
+            Ok(Some(cached)) => {
+                // Interface objects are allocated at runtime, those are always looked up again
+                offsets.interface.set_offsets(&offsets.library, process)?;
+                offsets.direct.local_controller =
+                    offsets.library.client + Address::from(cached.local_controller);
+                offsets.network = cached.network;
+                let resolved = resolve_direct_offsets(&mut offsets, process, |path| {
+                    !path.starts_with("interface.")
+                });
+
+                match offsets.validate(
+                    ResolutionMethod::Cache,
+                    ResolutionMethod::Cache,
+                    &resolved,
+                    &Overrides::default(),
+                ) {
+                    Ok(_) => {
+                        info!("Using cached offsets for client build {}", build);
+                        return Ok(offsets);
+                    }
+                    Err(e) => warn!("Cached offsets are incomplete, scanning: {:#}", e),
+                }
+            }
+            Ok(None) => info!("No cached offsets for client build {}, scanning", build),
+            Err(e) => warn!("Ignoring offset cache {}: {:#}", self.path.display(), e),
+        }
+
+        let offsets = Offsets::find_offsets(process)?;
+
+        if let Err(e) = self.store(&build, &offsets) {
+            warn!(
+                "Unable to write offset cache {}: {:#}",
+                self.path.display(),
+                e
+            );
+        }
+
+        Ok(offsets)
+    }
+
+    fn load(&self, build: &str) -> Result<Option<CachedOffsets>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let cached: CachedOffsets =
+            serde_json::from_str(&contents).context("Unable to parse offset cache")?;
+
+        Ok((cached.version == CACHE_VERSION && cached.client_build == build).then_some(cached))
+    }
+
+    /// Writes the entry for `build`. Offsets whose netvars came from the dump scan are refused:
+    /// the scan is a heuristic, and a wrong result would otherwise stick for the whole build
+    /// without anything noticing. They are looked for again on the next launch instead.
+    fn store(&self, build: &str, offsets: &Offsets) -> Result<()> {
+        ensure!(
+            !offsets.report.found().any(|offset| {
+                offset.name.starts_with("network.") && offset.method == Some(ResolutionMethod::Scan)
+            }),
+            "Netvars were found by scanning the client, not through the schema"
+        );
+
+        let cached = CachedOffsets {
+            version: CACHE_VERSION,
+            client_build: build.to_string(),
+            local_controller: u64::from(offsets.direct.local_controller)
+                .checked_sub(offsets.library.client.into())
+                .context("Local controller is not inside of the client library")?,
+            network: offsets.network.clone(),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Written aside and renamed, so a crash never leaves half a cache behind
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_string_pretty(&cached)?)?;
+        fs::rename(&temporary, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Identifies a build of the client library, by its GNU build-id when it has one and otherwise by
+/// a hash of its read-only segments.
+fn build_key(process: &impl MemorySource, client_base: u64) -> Result<String> {
+    let elf = process.module_elf(client_base)?;
+
+    if let Some(id) = elf.build_id()? {
+        return Ok(format!("build-id:{}", hex(&id)));
+    }
+
+    warn!("{} has no build-id, hashing it instead", CLIENT_LIB);
+
+    Ok(format!("fnv1a:{:016x}", content_hash(&elf)?))
+}
+
+/// FNV-1a over every non-writable loaded segment, the parts that only change with the build
+fn content_hash<D: ElfData>(elf: &Elf<D>) -> Result<u64> {
+    const CHUNK_SIZE: u64 = 0x10000;
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut chunk = vec![0u8; CHUNK_SIZE as usize];
+
+    for segment in elf.load_segments().filter(|ph| !ph.is_writable()) {
+        let mut position = segment.p_vaddr;
+        let end = segment.p_vaddr + segment.p_filesz;
+
+        while position < end {
+            let length = CHUNK_SIZE.min(end - position) as usize;
+            elf.data()
+                .read_vaddr(position, &mut chunk[..length])
+                .with_context(|| format!("Unable to read segment at {:#x}", position))?;
+
+            for &byte in &chunk[..length] {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+
+            position += length as u64;
+        }
+    }
+
+    Ok(hash)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{build_key, CachedOffsets, OffsetCache, CACHE_VERSION};
+    use crate::process::{
+        memory::Address,
+        offset_report::{OffsetReport, ResolutionMethod},
+        offsets::Offsets,
+        pid::Pid,
+        process::ProcessHandle,
+        source::MemorySource,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "make_it_fair_offset_cache_{}.json",
+            std::process::id()
+        ));
+        let cache = OffsetCache::new(&path);
+
+        let mut offsets = Offsets::default();
+        offsets.library.client = Address::from(0x7f00_0000_0000);
+        offsets.direct.local_controller = Address::from(0x7f00_0012_3450);
+        offsets.network.pawn.m_iHealth = Address::from(0x344);
+        offsets.report = OffsetReport::new(
+            &offsets,
+            ResolutionMethod::Signature,
+            ResolutionMethod::Schema,
+        );
+
+        assert!(cache.load("build-id:aa")?.is_none());
+
+        cache.store("build-id:aa", &offsets)?;
+        let cached: CachedOffsets = cache.load("build-id:aa")?.expect("Stored entry");
+
+        assert_eq!(cached.version, CACHE_VERSION);
+        assert_eq!(cached.local_controller, 0x12_3450);
+        assert_eq!(cached.network.pawn.m_iHealth, Address::from(0x344));
+
+        // A game update invalidates the entry
+        assert!(cache.load("build-id:bb")?.is_none());
+
+        // Scanned netvars aren't trusted enough to keep
+        let mut scanned = offsets.clone();
+        scanned.report = OffsetReport::new(
+            &offsets,
+            ResolutionMethod::Signature,
+            ResolutionMethod::Scan,
+        );
+        assert!(cache.store("build-id:cc", &scanned).is_err());
+        assert!(cache.load("build-id:cc")?.is_none());
+
+        std::fs::write(&path, "not json")?;
+        assert!(cache.load("build-id:aa").is_err());
+
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    /// The test process' own libc is keyed by its build-id
+    #[tokio::test]
+    async fn test_build_key() -> Result<()> {
+        let process = ProcessHandle::from_pid(Pid(std::process::id() as u64)).await?;
+        let base: u64 = process.get_module_base_address("libc.so.6")?.into();
+
+        let key = build_key(&process, base)?;
+        assert!(key.starts_with("build-id:"));
+        assert_eq!(key, build_key(&process, base)?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use super::{
+    memory::Address,
+    offsets::{Offsets, NETVARS},
+};
+
+/// How an offset was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ResolutionMethod {
+    /// Base address of a mapped module
+    Module,
+    /// Walking a module's `CreateInterface` list
+    Interface,
+    /// Byte signature scan
+    Signature,
+    /// The SchemaSystem's class bindings
+    Schema,
+    /// The client global pointing at a controller found in the entity list
+    EntityList,
+    /// Heuristic scan of the client dump
+    Scan,
+    /// Read back from the offset cache
+    Cache,
+    /// Pinned by the user's overrides file
+    Override,
+}
+
+impl Display for ResolutionMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ResolutionMethod::Module => "module",
+            ResolutionMethod::Interface => "interface",
+            ResolutionMethod::Signature => "signature",
+            ResolutionMethod::Schema => "schema",
+            ResolutionMethod::EntityList => "entities",
+            ResolutionMethod::Scan => "scan",
+            ResolutionMethod::Cache => "cache",
+            ResolutionMethod::Override => "override",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// One offset of `Offsets`, and whether it was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedOffset {
+    /// Path inside of `Offsets`, e.g. `network.pawn.m_iHealth`
+    pub name: String,
+    /// Without it nothing useful can be read, so resolution fails
+    pub required: bool,
+    /// `None` when the offset is missing
+    pub method: Option<ResolutionMethod>,
+    pub value: Address,
+    /// Other values found for it that weren't used, see `Conflict`
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A candidate a less preferred strategy found for an offset, disagreeing with the one used.
+/// Either strategy may be the broken one, so these are kept for the report rather than dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Conflict {
+    pub method: ResolutionMethod,
+    pub value: Address,
+}
+
+/// How a direct offset was found, by `resolve_direct_offsets`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectResolution {
+    pub path: &'static str,
+    pub method: ResolutionMethod,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ResolvedOffset {
+    pub fn is_found(&self) -> bool {
+        self.method.is_some()
+    }
+}
+
+/// What `Offsets::find_offsets` found, how, and what it couldn't find.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OffsetReport {
+    pub offsets: Vec<ResolvedOffset>,
+}
+
+impl OffsetReport {
+    /// Checks every offset, anything still null is missing. `direct` and `network` are the methods
+    /// used for those groups, libraries and interfaces are always looked up directly.
+    pub fn new(offsets: &Offsets, direct: ResolutionMethod, network: ResolutionMethod) -> Self {
+        let library = &offsets.library;
+        let interface = &offsets.interface;
+
+        use ResolutionMethod::{Interface, Module};
+
+        let mut entries = vec![
+            ("library.client".to_string(), library.client, true, Module),
+            ("library.engine".to_string(), library.engine, true, Module),
+            ("library.tier0".to_string(), library.tier0, true, Module),
+            ("library.schema".to_string(), library.schema, false, Module),
+            (
+                "interface.resource".to_string(),
+                interface.resource,
+                true,
+                Interface,
+            ),
+            (
+                "interface.entity".to_string(),
+                interface.entity,
+                true,
+                Interface,
+            ),
+            (
+                "interface.convar".to_string(),
+                interface.convar,
+                true,
+                Interface,
+            ),
+            (
+                "interface.player".to_string(),
+                interface.player,
+                true,
+                Interface,
+            ),
+            (
+                "direct.local_controller".to_string(),
+                offsets.direct.local_controller,
+                true,
+                direct,
+            ),
+        ];
+
+        entries.extend(NETVARS.iter().map(|netvar| {
+            (
+                netvar.path(),
+                (netvar.get)(&offsets.network),
+                netvar.is_required(),
+                network,
+            )
+        }));
+
+        Self {
+            offsets: entries
+                .into_iter()
+                .map(|(name, value, required, method)| ResolvedOffset {
+                    name,
+                    required,
+                    method: value.is_valid().then_some(method),
+                    value,
+                    conflicts: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    /// Records how individual offsets were found, for those not found like the rest of their
+    /// group, and what their other strategies disagreed on
+    pub fn set_methods(&mut self, resolved: &[DirectResolution]) {
+        for resolution in resolved {
+            if let Some(offset) = self
+                .offsets
+                .iter_mut()
+                .find(|offset| offset.name == resolution.path)
+            {
+                offset.method = offset.value.is_valid().then_some(resolution.method);
+                offset.conflicts = resolution.conflicts.clone();
+            }
+        }
+    }
+
+    /// Offsets whose strategies didn't agree on a value
+    pub fn conflicting(&self) -> impl Iterator<Item = &ResolvedOffset> {
+        self.offsets
+            .iter()
+            .filter(|offset| !offset.conflicts.is_empty())
+    }
+
+    /// Marks the offsets at `paths` as coming from the overrides file
+    pub fn mark_overridden<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        for path in paths {
+            if let Some(offset) = self.offsets.iter_mut().find(|offset| offset.name == path) {
+                offset.method = offset
+                    .value
+                    .is_valid()
+                    .then_some(ResolutionMethod::Override);
+            }
+        }
+    }
+
+    pub fn found(&self) -> impl Iterator<Item = &ResolvedOffset> {
+        self.offsets.iter().filter(|offset| offset.is_found())
+    }
+
+    pub fn missing(&self) -> impl Iterator<Item = &ResolvedOffset> {
+        self.offsets.iter().filter(|offset| !offset.is_found())
+    }
+
+    pub fn missing_required(&self) -> impl Iterator<Item = &ResolvedOffset> {
+        self.missing().filter(|offset| offset.required)
+    }
+
+    /// Fails, naming them, if any required offset is missing
+    pub fn ensure_complete(&self) -> Result<()> {
+        let missing: Vec<_> = self
+            .missing_required()
+            .map(|offset| offset.name.as_str())
+            .collect();
+
+        if !missing.is_empty() {
+            bail!("Missing required offsets: {}", missing.join(", "));
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for OffsetReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for offset in &self.offsets {
+            let requirement = if offset.required {
+                "required"
+            } else {
+                "optional"
+            };
+
+            match offset.method {
+                Some(method) => writeln!(
+                    f,
+                    "{:<46} {:>#16x}  {:<9}  {}",
+                    offset.name,
+                    u64::from(offset.value),
+                    method,
+                    requirement
+                )?,
+                None => writeln!(
+                    f,
+                    "{:<46} {:>16}  {:<9}  {}",
+                    offset.name, "missing", "-", requirement
+                )?,
+            }
+
+            for conflict in &offset.conflicts {
+                writeln!(
+                    f,
+                    "{:<46} {:>#16x}  {:<9}  conflicting",
+                    "",
+                    u64::from(conflict.value),
+                    conflict.method
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Conflict, DirectResolution, OffsetReport, ResolutionMethod};
+    use crate::process::{memory::Address, offsets::Offsets};
+
+    #[test]
+    fn test_report() {
+        let mut offsets = Offsets::default();
+        offsets.network.pawn.m_iHealth = Address::from(0x344);
+        offsets.network.pawn.m_ArmorValue = Address::from(0x2404);
+
+        let mut report = OffsetReport::new(
+            &offsets,
+            ResolutionMethod::Signature,
+            ResolutionMethod::Schema,
+        );
+
+        let health = report
+            .found()
+            .find(|offset| offset.name == "network.pawn.m_iHealth")
+            .expect("m_iHealth was set");
+        assert_eq!(health.method, Some(ResolutionMethod::Schema));
+        assert_eq!(health.value, Address::from(0x344));
+        assert_eq!(report.found().count(), 2);
+
+        assert!(report
+            .missing()
+            .any(|offset| offset.name == "network.pawn.m_pItemServices" && !offset.required));
+
+        let error = report.ensure_complete().unwrap_err().to_string();
+        assert!(error.contains("network.controller.m_hPawn"));
+        assert!(!error.contains("m_iHealth"));
+        assert!(!error.contains("m_pItemServices"));
+
+        assert!(report.to_string().contains("missing"));
+
+        report.mark_overridden(["network.pawn.m_ArmorValue"]);
+        let armor = report
+            .found()
+            .find(|offset| offset.name == "network.pawn.m_ArmorValue")
+            .expect("m_ArmorValue was set");
+        assert_eq!(armor.method, Some(ResolutionMethod::Override));
+        assert!(report.to_string().contains("override"));
+
+        report.set_methods(&[DirectResolution {
+            path: "network.pawn.m_iHealth",
+            method: ResolutionMethod::Signature,
+            conflicts: vec![Conflict {
+                method: ResolutionMethod::EntityList,
+                value: Address::from(0x348),
+            }],
+        }]);
+        let conflicting: Vec<_> = report.conflicting().collect();
+        assert_eq!(conflicting.len(), 1);
+        assert_eq!(conflicting[0].method, Some(ResolutionMethod::Signature));
+        assert!(report.to_string().contains("conflicting"));
+    }
+
+    #[test]
+    fn test_optional_missing() {
+        let mut report = OffsetReport::new(
+            &Offsets::default(),
+            ResolutionMethod::Cache,
+            ResolutionMethod::Cache,
+        );
+
+        for offset in &mut report.offsets {
+            if offset.required {
+                offset.method = Some(ResolutionMethod::Cache);
+            }
+        }
+
+        assert!(report.ensure_complete().is_ok());
+        assert!(report.missing().all(|offset| !offset.required));
+    }
+}
@@ -1,32 +1,35 @@
-use crate::constant::{self, CLIENT_LIB, ENGINE_LIB, TIER0_LIB};
+use crate::constant::{
+    CLIENT_LIB, CVAR_INTERFACE, ENGINE_LIB, RESOURCE_INTERFACE, SCHEMA_LIB, TIER0_LIB,
+};
 
 use super::{
     memory::{self, Address},
-    process::ProcessHandle,
+    netvars::{netvars, NetVar, ScanMarker},
+    offset_report::{DirectResolution, OffsetReport, ResolutionMethod},
+    overrides::Overrides,
+    schema::SchemaSystem,
+    source::MemorySource,
+    strategies::resolve_direct_offsets,
 };
-use anyhow::{Context, Result};
-use log::info;
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Offsets {
     pub interface: InterfaceOffsets,
     pub library: LibraryOffsets,
     pub direct: DirectOffsets,
 
     pub network: NetVarOffsets,
-}
 
-#[derive(Debug, Default)]
-pub struct NetVarOffsets {
-    pub controller: PlayerControllerOffsets,
-    pub pawn: PawnOffsets,
-    pub weapon_service: WeaponServiceOffsets,
-    pub money_service: MoneyServiceOffsets,
-    pub observer_service: ObserverServiceOffsets,
-    pub item_service: ItemServiceOffsets,
+    /// How each offset was found, left empty for offsets that weren't resolved in this process
+    #[serde(skip)]
+    pub report: OffsetReport,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct LibraryOffsets {
     // libclient.so
     pub client: Address,
@@ -36,9 +39,12 @@ pub struct LibraryOffsets {
 
     // libteir0.so
     pub tier0: Address,
+
+    // libschemasystem.so
+    pub schema: Address,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct InterfaceOffsets {
     pub resource: Address,
     pub entity: Address,
@@ -46,13 +52,22 @@ pub struct InterfaceOffsets {
     pub player: Address,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct DirectOffsets {
     pub local_controller: Address,
 }
 
 impl Offsets {
-    pub fn find_offsets(process: &ProcessHandle) -> Result<Offsets> {
+    pub fn find_offsets(process: &impl MemorySource) -> Result<Offsets> {
+        Self::find_offsets_with(process, &Overrides::default())
+    }
+
+    /// Resolves every offset, then pins the ones named in `overrides`. Lookups whose every result
+    /// is overridden are skipped, so a broken signature can be replaced without failing first.
+    pub fn find_offsets_with(
+        process: &impl MemorySource,
+        overrides: &Overrides,
+    ) -> Result<Offsets> {
         let mut offsets = Offsets::default();
 
         // Set Shared Object offsets
@@ -62,49 +77,149 @@ impl Offsets {
             .context("Unable to set Library Offsets")?;
 
         // Set Interface Offsets
-        offsets.interface.set_offsets(&offsets.library, process)?;
-
-        // Get local controller
-        offsets.direct.set_offsets(&offsets.library, process)?;
+        let interfaces = ["interface.resource", "interface.convar"];
+        if !interfaces.iter().all(|path| overrides.contains(path)) {
+            offsets.interface.set_offsets(&offsets.library, process)?;
+        }
 
         // Get Net Var Offsets
-        offsets.network.set_offsets(&offsets.library, process)?;
+        let network_method = offsets.network.set_offsets(&offsets.library, process)?;
+
+        // Derived interfaces and the local controller, some strategies need netvars
+        let resolved =
+            resolve_direct_offsets(&mut offsets, process, |path| overrides.contains(path));
+
+        overrides.apply(&mut offsets, process)?;
+
+        offsets.validate(
+            ResolutionMethod::Signature,
+            network_method,
+            &resolved,
+            overrides,
+        )?;
+
+        Ok(offsets)
+    }
+
+    /// Resolves what can be resolved from the libraries on disk, e.g. an `OfflineSource` over a
+    /// new build. Interfaces only exist in a running game, so they stay null and out of the
+    /// report, and netvars come from the dump scan since the schema is built at runtime too.
+    pub fn find_offsets_offline(process: &impl MemorySource) -> Result<Offsets> {
+        let mut offsets = Offsets::default();
+
+        offsets.library.client = process.get_module_base_address(CLIENT_LIB)?;
+        offsets.library.engine = process.get_module_base_address(ENGINE_LIB)?;
+        offsets.library.tier0 = process.get_module_base_address(TIER0_LIB)?;
+        offsets.library.schema = process
+            .get_module_base_address(SCHEMA_LIB)
+            .unwrap_or_default();
+
+        offsets
+            .network
+            .scan_client_dump(&offsets.library, process)?;
+        let resolved =
+            resolve_direct_offsets(&mut offsets, process, |path| path.starts_with("interface."));
+
+        let mut report = OffsetReport::new(
+            &offsets,
+            ResolutionMethod::Signature,
+            ResolutionMethod::Scan,
+        );
+        report.set_methods(&resolved);
+        report.offsets.retain(|offset| {
+            !offset.name.starts_with("interface.") && offset.name != "library.schema"
+        });
+
+        debug!("Offline offsets:\n{}", report);
+
+        for offset in report.missing() {
+            warn!("Unable to find offset {}", offset.name);
+        }
+
+        report.ensure_complete()?;
+        offsets.report = report;
 
         Ok(offsets)
     }
+
+    /// Builds the report for these offsets, failing if any required offset is missing. Missing
+    /// optional offsets only leave their `Player` fields empty. `resolved` names the method of
+    /// offsets found differently from the rest of their group.
+    pub fn validate(
+        &mut self,
+        direct: ResolutionMethod,
+        network: ResolutionMethod,
+        resolved: &[DirectResolution],
+        overrides: &Overrides,
+    ) -> Result<&OffsetReport> {
+        self.report = OffsetReport::new(self, direct, network);
+        self.report.set_methods(resolved);
+        self.report.mark_overridden(overrides.paths());
+
+        debug!("Offsets:\n{}", self.report);
+
+        for offset in self.report.missing() {
+            warn!("Unable to find offset {}", offset.name);
+        }
+
+        self.report.ensure_complete()?;
+
+        Ok(&self.report)
+    }
+
+    /// The field at `path`, e.g. `direct.local_controller` or `network.pawn.m_iHealth`
+    pub fn field_mut(&mut self, path: &str) -> Option<&mut Address> {
+        Some(match path {
+            "library.client" => &mut self.library.client,
+            "library.engine" => &mut self.library.engine,
+            "library.tier0" => &mut self.library.tier0,
+            "library.schema" => &mut self.library.schema,
+            "interface.resource" => &mut self.interface.resource,
+            "interface.entity" => &mut self.interface.entity,
+            "interface.convar" => &mut self.interface.convar,
+            "interface.player" => &mut self.interface.player,
+            "direct.local_controller" => &mut self.direct.local_controller,
+            path => {
+                let netvar = NETVARS.iter().find(|netvar| netvar.path() == path)?;
+                (netvar.get_mut)(&mut self.network)
+            }
+        })
+    }
 }
 
 impl LibraryOffsets {
-    pub fn set_offsets(&mut self, process: &ProcessHandle) -> Result<()> {
+    /// Finds the libraries offsets are relative to. The schema system is optional, without it
+    /// netvars come from the dump scan instead.
+    pub fn set_offsets(&mut self, process: &impl MemorySource) -> Result<()> {
         self.client = process.get_module_base_address(CLIENT_LIB)?;
         self.engine = process.get_module_base_address(ENGINE_LIB)?;
         self.tier0 = process.get_module_base_address(TIER0_LIB)?;
+        self.schema = process
+            .get_module_base_address(SCHEMA_LIB)
+            .unwrap_or_else(|e| {
+                warn!("Netvars will be scanned for: {:#}", e);
+                Address::NULL
+            });
 
         Ok(())
     }
 }
 
 impl InterfaceOffsets {
+    /// Looks up the interfaces exported through `CreateInterface`. The entity and player lists
+    /// are derived from them by `resolve_direct_offsets`.
     pub fn set_offsets(
         &mut self,
         library_offsets: &LibraryOffsets,
-        process: &ProcessHandle,
+        process: &impl MemorySource,
     ) -> Result<()> {
         self.resource = process
-            .get_interface_offset(library_offsets.engine.into(), "GameResourceServiceClientV0")?
+            .get_versioned_interface(library_offsets.engine.into(), RESOURCE_INTERFACE)
             .context("Not able to find offset")?
             .into();
 
-        let resource_u64: u64 = self.resource.into();
-        let player_address_ptr = resource_u64 + constant::ENTITY_OFFSET;
-
-        let entity_ptr = process.read_u64(player_address_ptr)?;
-
-        self.entity = entity_ptr.into();
-        self.player = (entity_ptr + 0x10).into();
-
         let convar_offset = process
-            .get_interface_offset(library_offsets.tier0.into(), "VEngineCvar0")?
+            .get_versioned_interface(library_offsets.tier0.into(), CVAR_INTERFACE)
             .context("Unable to read cvar offset")?;
         self.convar = convar_offset.into();
 
@@ -112,278 +227,286 @@ impl InterfaceOffsets {
     }
 }
 
-impl DirectOffsets {
+impl NetVarOffsets {
+    /// Resolves every netvar through the schema system, falling back to scanning the client dump
+    /// if the schema can't be walked. Returns which of the two was used.
     pub fn set_offsets(
         &mut self,
         library_offsets: &LibraryOffsets,
-        process: &ProcessHandle,
-    ) -> Result<()> {
-        let direct_address_ptr = process
-            .scan_pattern(
-                &[
-                    0x48, 0x83, 0x3D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0F, 0x95, 0xC0, 0xC3,
-                ],
-                "xxx????xxxxx".as_bytes(),
-                library_offsets.client.into(),
-            )?
-            .context("Unable to find local player controller")?;
-
-        self.local_controller = process
-            .get_relative_address(direct_address_ptr, 0x03, 0x08)?
-            .into();
+        process: &impl MemorySource,
+    ) -> Result<ResolutionMethod> {
+        let schema = SchemaSystem::from_process(process, library_offsets)
+            .and_then(|schema| self.set_from_schema(&schema));
+
+        if let Err(e) = schema {
+            warn!(
+                "Unable to resolve netvars through the schema system, scanning instead: {:#}",
+                e
+            );
+
+            *self = NetVarOffsets::default();
+            self.scan_client_dump(library_offsets, process)?;
+
+            return Ok(ResolutionMethod::Scan);
+        }
+
+        Ok(ResolutionMethod::Schema)
+    }
+
+    /// Looks up every netvar in its class. Optional ones that are missing stay null, a required
+    /// one that is missing fails the whole schema so the scan can take over.
+    fn set_from_schema<M: MemorySource>(&mut self, schema: &SchemaSystem<'_, M>) -> Result<()> {
+        for netvar in NETVARS {
+            let offset = match schema.field_offset(netvar.class, netvar.field) {
+                Ok(offset) => offset,
+                Err(e) if netvar.is_required() => return Err(e),
+                Err(_) => None,
+            };
+
+            match offset {
+                Some(offset) => *(netvar.get_mut)(self) = Address::from(offset as u64),
+                None if netvar.is_required() => {
+                    bail!("Schema has no field {}::{}", netvar.class, netvar.field)
+                }
+                None => {}
+            }
+        }
 
         Ok(())
     }
-}
 
-impl NetVarOffsets {
-    pub fn set_offsets(
+    /// Looks for netvar names next to `MNetworkEnable` markers in a dump of the client. Only the
+    /// first match of a name is kept, whichever class it came from.
+    fn scan_client_dump(
         &mut self,
         library_offsets: &LibraryOffsets,
-        process: &ProcessHandle,
+        process: &impl MemorySource,
     ) -> Result<()> {
-        let base: u64 = library_offsets.client.into();
-
-        let client_module_size = process.module_size(library_offsets.client.into())?;
         let client_dump = process.dump_module(library_offsets.client.into())?;
+        self.scan_dump(&client_dump, library_offsets.client.into());
+
+        Ok(())
+    }
 
-        for i in (0..=(client_module_size - 8)).rev().step_by(8) {
+    /// The scan itself, over a dump of the client mapped at `base`. Pointers only count when they
+    /// land inside of the dump, and nothing is read past its end.
+    fn scan_dump(&mut self, client_dump: &[u8], base: u64) {
+        let client_module_size = client_dump.len() as u64;
+        let end = base + client_module_size;
+
+        let Some(last) = client_module_size.checked_sub(8) else {
+            return;
+        };
+
+        let mut by_field: HashMap<&str, Vec<&NetVar>> = HashMap::new();
+        for netvar in NETVARS {
+            by_field.entry(netvar.field).or_default().push(netvar);
+        }
+
+        for i in (0..=last).rev().step_by(8) {
             let mut network_enable = false;
 
-            let mut name_pointer = memory::read_u64_vec(&client_dump, i);
-            if name_pointer >= base && name_pointer <= base + client_module_size {
-                name_pointer = memory::read_u64_vec(&client_dump, name_pointer - base);
-                if name_pointer >= base && name_pointer <= base + client_module_size {
-                    let name = memory::read_string_vec(&client_dump, name_pointer - base);
-                    if name.to_lowercase() == "MNetworkEnable".to_lowercase() {
+            let Some(word) = memory::read_pod_at::<u64>(client_dump, i) else {
+                continue;
+            };
+
+            let mut name_pointer = word;
+            if (base..end).contains(&name_pointer) {
+                name_pointer = memory::read_pod_at::<u64>(client_dump, name_pointer - base)
+                    .unwrap_or_default();
+                if (base..end).contains(&name_pointer) {
+                    let name = memory::read_str_at(client_dump, name_pointer - base);
+                    if name.is_some_and(|name| name.eq_ignore_ascii_case("MNetworkEnable")) {
                         network_enable = true;
                     }
                 }
             }
 
             let name_ptr = match network_enable {
-                true => memory::read_u64_vec(&client_dump, i + 0x08),
-                false => memory::read_u64_vec(&client_dump, i),
+                true => memory::read_pod_at::<u64>(client_dump, i + 0x08),
+                false => Some(word),
             };
 
-            if name_ptr < base || name_ptr > base + client_module_size {
+            let Some(name_ptr) = name_ptr else {
+                continue;
+            };
+
+            if !(base..end).contains(&name_ptr) {
                 continue;
             }
 
-            let netvar_name = memory::read_string_vec(&client_dump, name_ptr - base);
+            let Some(netvar_name) = memory::read_str_at(client_dump, name_ptr - base) else {
+                continue;
+            };
 
-            match netvar_name.as_str() {
-                "m_sSanitizedPlayerName" => {
-                    if !network_enable || self.controller.m_iszPlayerName.is_valid() {
-                        continue;
-                    }
+            let Some(netvars) = by_field.get(netvar_name.as_str()) else {
+                continue;
+            };
 
-                    self.controller.m_iszPlayerName =
-                        memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
+            for netvar in netvars {
+                if netvar.scan.marker == ScanMarker::Networked && !network_enable {
+                    continue;
                 }
-                "m_hPawn" => {
-                    if !network_enable || self.controller.m_hPawn.is_valid() {
-                        continue;
-                    }
 
-                    self.controller.m_hPawn = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
+                let value = (netvar.get_mut)(self);
+                if value.is_valid() {
+                    continue;
                 }
-                "m_iCompTeammateColor" => {
-                    if self.controller.m_hPawn.is_valid() {
-                        continue;
-                    }
 
-                    self.controller.m_iCompTeammateColor =
-                        memory::read_u32_vec(&client_dump, i + 0x10);
+                if let Some(offset) =
+                    memory::read_pod_at::<u32>(client_dump, i + netvar.scan.offset)
+                {
+                    *value = Address::from(offset as u64);
                 }
-                "m_iPing" => {
-                    if !network_enable || self.controller.m_iPing.is_valid() {
-                        continue;
-                    }
+            }
+        }
+    }
+}
 
-                    self.controller.m_iPing = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_pInGameMoneyServices" => {
-                    if self.controller.m_pInGameMoneyServices.is_valid() {
-                        continue;
-                    }
+netvars! {
+    controller: PlayerControllerOffsets {
+        m_iszPlayerName: String = ("CCSPlayerController", "m_sSanitizedPlayerName"), Required, scan(Networked, 0x18);
+        m_hPawn: Handle = ("CCSPlayerController", "m_hPawn"), Required, scan(Networked, 0x18);
+        m_bIsLocalPlayerController: Bool = ("CCSPlayerController", "m_bIsLocalPlayerController"), Optional, scan(Networked, 0x18);
+        m_iCompTeammateColor: I32 = ("CCSPlayerController", "m_iCompTeammateColor"), Optional, scan(Any, 0x10);
+        m_iPing: I32 = ("CCSPlayerController", "m_iPing"), Optional, scan(Networked, 0x18);
+        m_pInGameMoneyServices: Pointer = ("CCSPlayerController", "m_pInGameMoneyServices"), Optional, scan(Any, 0x10);
+        m_steamID: U64 = ("CCSPlayerController", "m_steamID"), Optional, scan(Networked, 0x18);
+    }
 
-                    self.controller.m_pInGameMoneyServices =
-                        memory::read_u32_vec(&client_dump, i + 0x10);
-                }
-                "m_steamID" => {
-                    if !network_enable || self.controller.m_steamID.is_valid() {
-                        continue;
-                    }
-                    self.controller.m_steamID = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_iHealth" => {
-                    if !network_enable || self.pawn.m_iHealth.is_valid() {
-                        continue;
-                    }
-                    self.pawn.m_iHealth = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_ArmorValue" => {
-                    if !network_enable || self.pawn.m_ArmorValue.is_valid() {
-                        continue;
-                    }
-                    self.pawn.m_ArmorValue = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_iTeamNum" => {
-                    if !network_enable || self.pawn.m_iTeamNum.is_valid() {
-                        continue;
-                    }
+    pawn: PawnOffsets {
+        m_iHealth: I32 = ("C_CSPlayerPawn", "m_iHealth"), Required, scan(Networked, 0x18);
+        m_ArmorValue: I32 = ("C_CSPlayerPawn", "m_ArmorValue"), Optional, scan(Networked, 0x18);
+        m_iTeamNum: U8 = ("C_CSPlayerPawn", "m_iTeamNum"), Required, scan(Networked, 0x18);
+        m_lifeState: U8 = ("C_CSPlayerPawn", "m_lifeState"), Required, scan(Networked, 0x18);
+        m_pClippingWeapon: Pointer = ("C_CSPlayerPawn", "m_pClippingWeapon"), Optional, scan(Any, 0x10);
+        m_vOldOrigin: Vec3 = ("C_CSPlayerPawn", "m_vOldOrigin"), Required, scan(Any, 0x08);
+        m_angEyeAngles: Vec3 = ("C_CSPlayerPawn", "m_angEyeAngles"), Optional, scan(Any, 0x10);
+        m_pWeaponServices: Pointer = ("C_CSPlayerPawn", "m_pWeaponServices"), Optional, scan(Any, 0x08);
+        m_pObserverServices: Pointer = ("C_CSPlayerPawn", "m_pObserverServices"), Optional, scan(Any, 0x08);
+        m_pItemServices: Pointer = ("C_CSPlayerPawn", "m_pItemServices"), Optional, scan(Any, 0x08);
+    }
 
-                    self.pawn.m_iTeamNum = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_lifeState" => {
-                    if !network_enable || self.pawn.m_lifeState.is_valid() {
-                        continue;
-                    }
+    weapon_service: WeaponServiceOffsets {
+        m_hActiveWeapon: Handle = ("CPlayer_WeaponServices", "m_hActiveWeapon"), Optional, scan(Networked, 0x18);
+        m_hMyWeapons: HandleVector = ("CPlayer_WeaponServices", "m_hMyWeapons"), Optional, scan(Any, 0x08);
+        m_iAmmo: U16 = ("CPlayer_WeaponServices", "m_iAmmo"), Optional, scan(Networked, 0x18);
+    }
 
-                    self.pawn.m_lifeState = memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_pClippingWeapon" => {
-                    if self.pawn.m_pClippingWeapon.is_valid() {
-                        continue;
-                    }
+    weapon: WeaponOffsets {
+        m_iClip1: I32 = ("C_BasePlayerWeapon", "m_iClip1"), Optional, scan(Networked, 0x18);
+        m_pReserveAmmo: I32 = ("C_BasePlayerWeapon", "m_pReserveAmmo"), Optional, scan(Networked, 0x18);
+        m_bInReload: Bool = ("C_CSWeaponBase", "m_bInReload"), Optional, scan(Any, 0x10);
+        m_nSubclassID: U32 = ("C_BaseEntity", "m_nSubclassID"), Optional, scan(Networked, 0x18);
+    }
 
-                    self.pawn.m_pClippingWeapon = memory::read_u32_vec(&client_dump, i + 0x10);
-                }
-                "m_vOldOrigin" => {
-                    if self.pawn.m_vOldOrigin.is_valid() {
-                        continue;
-                    }
+    weapon_data: WeaponDataOffsets {
+        m_nPrimaryAmmoType: I8 = ("CBasePlayerWeaponVData", "m_nPrimaryAmmoType"), Optional, scan(Any, 0x10);
+    }
 
-                    self.pawn.m_vOldOrigin = memory::read_u32_vec(&client_dump, i + 0x08);
-                }
-                "m_angEyeAngles" => {
-                    if self.pawn.m_angEyeAngles.is_valid() {
-                        continue;
-                    }
+    money_service: MoneyServiceOffsets {
+        m_iAccount: I32 = ("CCSPlayerController_InGameMoneyServices", "m_iAccount"), Optional, scan(Any, 0x10);
+    }
 
-                    self.pawn.m_angEyeAngles = memory::read_u32_vec(&client_dump, i + 0x10);
-                }
-                "m_pWeaponServices" => {
-                    if self.pawn.m_pWeaponServices.is_valid() {
-                        continue;
-                    }
+    observer_service: ObserverServiceOffsets {
+        m_hObserverTarget: Handle = ("CPlayer_ObserverServices", "m_hObserverTarget"), Optional, scan(Any, 0x08);
+    }
 
-                    self.pawn.m_pWeaponServices = memory::read_u32_vec(&client_dump, i + 0x08);
-                }
-                "m_pObserverServices" => {
-                    if self.pawn.m_pObserverServices.is_valid() {
-                        continue;
-                    }
+    econ_item: EconItemOffsets {
+        m_AttributeManager: Embedded = ("C_EconEntity", "m_AttributeManager"), Optional, scan(Networked, 0x18);
+        m_Item: Embedded = ("C_AttributeContainer", "m_Item"), Optional, scan(Networked, 0x18);
+        m_iItemDefinitionIndex: U16 = ("C_EconItemView", "m_iItemDefinitionIndex"), Optional, scan(Networked, 0x18);
+    }
 
-                    self.pawn.m_pObserverServices = memory::read_u32_vec(&client_dump, i + 0x08);
-                }
-                "m_pItemServices" => {
-                    if self.pawn.m_pItemServices.is_valid() {
-                        continue;
-                    }
+    game_rules_proxy: GameRulesProxyOffsets {
+        m_pGameRules: Pointer = ("C_CSGameRulesProxy", "m_pGameRules"), Optional, scan(Any, 0x08);
+    }
 
-                    self.pawn.m_pItemServices = memory::read_u32_vec(&client_dump, i + 0x08);
-                }
-                "m_hActiveWeapon" => {
-                    if !network_enable || self.weapon_service.m_hActiveWeapon.is_valid() {
-                        continue;
-                    }
+    game_rules: GameRulesOffsets {
+        m_bWarmupPeriod: Bool = ("C_CSGameRules", "m_bWarmupPeriod"), Optional, scan(Networked, 0x18);
+        m_bFreezePeriod: Bool = ("C_CSGameRules", "m_bFreezePeriod"), Optional, scan(Networked, 0x18);
+        m_bGamePaused: Bool = ("C_CSGameRules", "m_bGamePaused"), Optional, scan(Networked, 0x18);
+        m_bMatchWaitingForResume: Bool = ("C_CSGameRules", "m_bMatchWaitingForResume"), Optional, scan(Networked, 0x18);
+        m_iRoundTime: I32 = ("C_CSGameRules", "m_iRoundTime"), Optional, scan(Networked, 0x18);
+        m_fRoundStartTime: F32 = ("C_CSGameRules", "m_fRoundStartTime"), Optional, scan(Networked, 0x18);
+        m_totalRoundsPlayed: I32 = ("C_CSGameRules", "m_totalRoundsPlayed"), Optional, scan(Networked, 0x18);
+        m_iRoundWinStatus: I32 = ("C_CSGameRules", "m_iRoundWinStatus"), Optional, scan(Networked, 0x18);
+    }
 
-                    self.weapon_service.m_hActiveWeapon =
-                        memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                "m_hMyWeapons" => {
-                    if self.weapon_service.m_hMyWeapons.is_valid() {
-                        continue;
-                    }
+    team: TeamOffsets {
+        m_iTeamNum: U8 = ("C_Team", "m_iTeamNum"), Optional, scan(Networked, 0x18);
+        m_iScore: I32 = ("C_Team", "m_iScore"), Optional, scan(Networked, 0x18);
+    }
 
-                    self.weapon_service.m_hMyWeapons = memory::read_u32_vec(&client_dump, i + 0x08);
-                }
-                "m_iAccount" => {
-                    if self.money_service.m_iAccount.is_valid() {
-                        continue;
-                    }
+    item_service: ItemServiceOffsets {
+        m_bHasDefuser: Bool = ("CCSPlayer_ItemServices", "m_bHasDefuser"), Optional, scan(Any, 0x10);
+        m_bHasHelmet: Bool = ("CCSPlayer_ItemServices", "m_bHasHelmet"), Optional, scan(Networked, 0x18);
+    }
+}
 
-                    self.money_service.m_iAccount = memory::read_u32_vec(&client_dump, i + 0x10);
-                }
-                "m_hObserverTarget" => {
-                    if self.observer_service.m_hObserverTarget.is_valid() {
-                        continue;
-                    }
-                    self.observer_service.m_hObserverTarget =
-                        memory::read_u32_vec(&client_dump, i + 0x08);
-                }
-                "m_bHasDefuser" => {
-                    if self.item_service.m_bHasDefuser.is_valid() {
-                        continue;
-                    }
-                    self.item_service.m_bHasDefuser = memory::read_u32_vec(&client_dump, i + 0x10);
-                }
-                "m_bHasHelmet" => {
-                    if !network_enable || self.item_service.m_bHasHelmet.is_valid() {
-                        continue;
-                    }
+#[cfg(test)]
+mod test {
+    use super::{LibraryOffsets, NetVarOffsets};
+    use crate::process::{
+        maps::MemoryMap,
+        memory::Address,
+        source::{test::BufferSource, MemorySource},
+    };
+    use anyhow::{bail, Result};
 
-                    self.item_service.m_bHasHelmet =
-                        memory::read_u32_vec(&client_dump, i + 0x08 + 0x10);
-                }
-                _ => {}
-            }
-        }
+    const BASE: u64 = 0x1000;
 
-        Ok(())
-    }
-}
+    /// The game's libraries, but no schema system
+    struct Libraries;
 
-#[allow(non_snake_case)]
-#[derive(Debug, Default)]
-pub struct PlayerControllerOffsets {
-    pub m_iszPlayerName: Address,        // string (m_iszPlayerName)
-    pub m_hPawn: Address,                // pointer -> Pawn (m_hPawn)
-    pub m_iCompTeammateColor: Address,   // i32 (m_iCompTeammateColor)
-    pub m_iPing: Address,                // i32 (m_iPing)
-    pub m_pInGameMoneyServices: Address, // pointer -> MoneyServices (m_pInGameMoneyServices)
-    pub m_steamID: Address,              // u64 (m_steamID)
-}
+    impl MemorySource for Libraries {
+        fn read_into(&self, _address: u64, _buffer: &mut [u8]) -> Result<()> {
+            bail!("Nothing is mapped")
+        }
 
-#[allow(non_snake_case)]
-#[derive(Debug, Default)]
-pub struct PawnOffsets {
-    pub m_iHealth: Address,           // i32 (m_iHealth)
-    pub m_ArmorValue: Address,        // i32 (m_ArmorValue)
-    pub m_iTeamNum: Address,          // i32 (m_iTeamNum)
-    pub m_lifeState: Address,         // i32 (m_lifeState)
-    pub m_pClippingWeapon: Address,   // pointer -> WeaponBase (m_pClippingWeapon)
-    pub m_vOldOrigin: Address,        // vec3 (m_vOldOrigin)
-    pub m_angEyeAngles: Address,      // vec3? (m_angEyeAngles)
-    pub m_pWeaponServices: Address,   // pointer -> WeaponServices (m_pWeaponServices)
-    pub m_pObserverServices: Address, // pointer -> ObserverServices (m_pObserverServices)
-    pub m_pItemServices: Address,     // pointer -> ItemServices (m_pItemServices)
-}
+        fn memory_map(&self) -> Result<MemoryMap> {
+            MemoryMap::parse(
+                "10000-11000 r-xp 00000000 fd:01 1 /game/libclient.so\n\
+                 20000-21000 r-xp 00000000 fd:01 2 /game/libengine2.so\n\
+                 30000-31000 r-xp 00000000 fd:01 3 /game/libtier0.so",
+            )
+        }
+    }
 
-#[allow(non_snake_case)]
-#[derive(Debug, Default)]
-pub struct WeaponServiceOffsets {
-    pub m_hActiveWeapon: Address, // pointer -> Weapon (m_hActiveWeapon)
-    pub m_hMyWeapons: Address,    // pointer -> Vec<pointer -> Weapon> (m_hMyWeapons)
-}
+    #[test]
+    fn test_optional_schema() -> Result<()> {
+        let mut library = LibraryOffsets::default();
+        library.set_offsets(&Libraries)?;
 
-#[allow(non_snake_case)]
-#[derive(Debug, Default)]
-pub struct MoneyServiceOffsets {
-    pub m_iAccount: Address, // i32 (m_iAccount)
-}
+        assert_eq!(library.client, Address::from(0x10000));
+        assert_eq!(library.tier0, Address::from(0x30000));
+        assert!(library.schema.is_null());
 
-#[allow(non_snake_case)]
-#[derive(Debug, Default)]
-pub struct ObserverServiceOffsets {
-    pub m_hObserverTarget: Address, // pointer -> Pawn (m_hObserverTarget)
-}
+        Ok(())
+    }
 
-#[allow(non_snake_case)]
-#[derive(Debug, Default)]
-pub struct ItemServiceOffsets {
-    pub m_bHasDefuser: Address, // bool (m_bHasDefuser)
-    pub m_bHasHelmet: Address,  // bool (m_bHasHelmet)
+    #[test]
+    fn test_scan_dump() {
+        let mut dump = BufferSource::new(BASE, 0x100);
+
+        // 0x40 points at a pointer to the marker, its name follows at 0x48 and its offset at 0x58
+        dump.put_u64(BASE + 0x40, BASE + 0x20);
+        dump.put_u64(BASE + 0x20, BASE + 0xA0);
+        dump.put(BASE + 0xA0, b"MNetworkEnable\0");
+        dump.put_u64(BASE + 0x48, BASE + 0x80);
+        dump.put(BASE + 0x80, b"m_iHealth\0");
+        dump.put(BASE + 0x58, &0x344u32.to_le_bytes());
+
+        // A pointer to just past the end of the dump, the last word
+        dump.put_u64(BASE + 0xF8, BASE + 0x100);
+
+        let mut offsets = NetVarOffsets::default();
+        offsets.scan_dump(&dump.data, BASE);
+        assert_eq!(offsets.pawn.m_iHealth, Address::from(0x344));
+
+        // Smaller than a pointer
+        let mut offsets = NetVarOffsets::default();
+        offsets.scan_dump(&[0; 4], BASE);
+        assert!(offsets.pawn.m_iHealth.is_null());
+    }
 }
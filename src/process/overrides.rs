@@ -0,0 +1,293 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{bail, ensure, Context, Result};
+use log::info;
+use serde::Deserialize;
+
+use super::{offsets::Offsets, signature::Signature, source::MemorySource};
+use crate::constant::CLIENT_LIB;
+
+/// Path of an overrides file to load, `.toml` or `.json`
+pub const OVERRIDES_PATH_VAR: &str = "MAKE_IT_FAIR_OVERRIDES";
+
+/// A number written either as an integer or as a hex string, since JSON has no hex literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "NumberRepr")]
+pub struct Number(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberRepr {
+    Integer(u64),
+    Text(String),
+}
+
+impl TryFrom<NumberRepr> for Number {
+    type Error = String;
+
+    fn try_from(value: NumberRepr) -> Result<Self, Self::Error> {
+        match value {
+            NumberRepr::Integer(value) => Ok(Number(value)),
+            NumberRepr::Text(text) => {
+                let parsed = match text.strip_prefix("0x").or(text.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => text.parse(),
+                };
+
+                parsed
+                    .map(Number)
+                    .map_err(|e| format!("Invalid number '{}': {}", text, e))
+            }
+        }
+    }
+}
+
+/// What one field of `Offsets` is pinned to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Override {
+    /// A netvar's offset as is. Anything else is taken relative to `libclient.so`
+    Value(Number),
+    /// An address relative to the base of `module`
+    Relative { module: String, offset: Number },
+    /// A replacement signature, searched for in `module` (`libclient.so` by default). When
+    /// `operand` and `length` are given the match is a RIP relative instruction, and the address
+    /// it references is used, otherwise the match itself is.
+    Signature {
+        signature: String,
+        module: Option<String>,
+        operand: Option<u64>,
+        length: Option<u64>,
+    },
+}
+
+/// Fields of `Offsets` pinned by the user, keyed by their path, e.g. `direct.local_controller` or
+/// `network.pawn.m_iHealth`.
+///
+/// ```toml
+/// "network.pawn.m_iHealth" = "0x344"
+/// "direct.local_controller" = { signature = "48 83 3D ? ? ? ? 00 0F 95 C0 C3", operand = 3, length = 8 }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Overrides {
+    overrides: BTreeMap<String, Override>,
+}
+
+impl Overrides {
+    /// Reads an overrides file, TOML or JSON depending on its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Unable to read overrides '{}'", path.display()))?;
+
+        let overrides: Overrides = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            Some("json") => serde_json::from_str(&contents)?,
+            _ => bail!("Overrides '{}' are neither .toml nor .json", path.display()),
+        };
+
+        overrides.check()?;
+
+        info!(
+            "Loaded {} offset overrides from {}",
+            overrides.overrides.len(),
+            path.display()
+        );
+
+        Ok(overrides)
+    }
+
+    /// The file named by `$MAKE_IT_FAIR_OVERRIDES`, no overrides if it isn't set
+    pub fn from_env() -> Result<Self> {
+        match std::env::var_os(OVERRIDES_PATH_VAR) {
+            Some(path) if !path.is_empty() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Every path has to name a field, and only netvars take plain values without a module
+    fn check(&self) -> Result<()> {
+        let mut offsets = Offsets::default();
+
+        for (path, value) in &self.overrides {
+            ensure!(
+                offsets.field_mut(path).is_some(),
+                "Override for unknown offset '{}'",
+                path
+            );
+
+            if path.starts_with("network.") {
+                ensure!(
+                    matches!(value, Override::Value(_)),
+                    "Netvar override '{}' must be a plain offset",
+                    path
+                );
+            }
+
+            if let Override::Signature { signature, .. } = value {
+                Signature::parse(signature)
+                    .with_context(|| format!("Invalid signature for '{}'", path))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.overrides.contains_key(path)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.overrides.keys().map(String::as_str)
+    }
+
+    /// Writes every override into `offsets`, resolving modules and signatures against `process`
+    pub fn apply(&self, offsets: &mut Offsets, process: &impl MemorySource) -> Result<()> {
+        for (path, value) in &self.overrides {
+            let resolved = self
+                .resolve(path, value, process)
+                .with_context(|| format!("Unable to apply override for '{}'", path))?;
+
+            let field = offsets
+                .field_mut(path)
+                .with_context(|| format!("Override for unknown offset '{}'", path))?;
+
+            *field = resolved.into();
+        }
+
+        Ok(())
+    }
+
+    fn resolve(&self, path: &str, value: &Override, process: &impl MemorySource) -> Result<u64> {
+        match value {
+            Override::Value(Number(value)) if path.starts_with("network.") => Ok(*value),
+            Override::Value(Number(offset)) => {
+                Ok(u64::from(process.get_module_base_address(CLIENT_LIB)?) + offset)
+            }
+            Override::Relative {
+                module,
+                offset: Number(offset),
+            } => Ok(u64::from(process.get_module_base_address(module)?) + offset),
+            Override::Signature {
+                signature,
+                module,
+                operand,
+                length,
+            } => {
+                let module = module.as_deref().unwrap_or(CLIENT_LIB);
+                let module = process.get_module(module)?;
+                let address = process.find_signature(&module, &Signature::parse(signature)?)?;
+
+                match (operand, length) {
+                    (Some(operand), Some(length)) => {
+                        process.get_relative_address(address, *operand, *length)
+                    }
+                    (None, None) => Ok(address),
+                    _ => bail!("Signature overrides need both an operand and a length, or neither"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Number, Override, Overrides};
+    use crate::process::{
+        maps::MemoryMap, memory::Address, offsets::Offsets, source::MemorySource,
+    };
+    use anyhow::{Context, Result};
+
+    /// `libclient.so` mapped at 0x10000, with a `lea rax, [rip + 0x100]` at 0x10020
+    struct Client {
+        data: Vec<u8>,
+    }
+
+    impl Client {
+        fn new() -> Self {
+            let mut data = vec![0u8; 0x1000];
+            data[0x20..0x27].copy_from_slice(&[0x48, 0x8D, 0x05, 0x00, 0x01, 0x00, 0x00]);
+            Self { data }
+        }
+    }
+
+    impl MemorySource for Client {
+        fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+            let start = address.checked_sub(0x10000).context("Below client")? as usize;
+            let bytes = self
+                .data
+                .get(start..start + buffer.len())
+                .context("Past client")?;
+            buffer.copy_from_slice(bytes);
+            Ok(())
+        }
+
+        fn memory_map(&self) -> Result<MemoryMap> {
+            MemoryMap::parse("10000-11000 r-xp 00000000 fd:01 1 /game/libclient.so")
+        }
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let toml: Overrides = toml::from_str(
+            r#"
+            "network.pawn.m_iHealth" = "0x344"
+            "interface.convar" = { module = "libtier0.so", offset = 4096 }
+            "direct.local_controller" = { signature = "48 8D 05 ? ? ? ?", operand = 3, length = 7 }
+            "#,
+        )?;
+        let json: Overrides = serde_json::from_str(
+            r#"{
+                "network.pawn.m_iHealth": 836,
+                "interface.convar": { "module": "libtier0.so", "offset": "0x1000" },
+                "direct.local_controller": { "signature": "48 8D 05 ? ? ? ?", "operand": 3, "length": 7 }
+            }"#,
+        )?;
+
+        assert_eq!(toml, json);
+        assert_eq!(
+            toml.overrides["network.pawn.m_iHealth"],
+            Override::Value(Number(0x344))
+        );
+        toml.check()?;
+
+        let unknown: Overrides = toml::from_str(r#""network.pawn.m_iNothing" = 1"#)?;
+        assert!(unknown.check().is_err());
+
+        let relative_netvar: Overrides = toml::from_str(
+            r#""network.pawn.m_iHealth" = { module = "libclient.so", offset = 1 }"#,
+        )?;
+        assert!(relative_netvar.check().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply() -> Result<()> {
+        let overrides: Overrides = toml::from_str(
+            r#"
+            "network.pawn.m_iHealth" = "0x344"
+            "interface.entity" = "0x800"
+            "direct.local_controller" = { signature = "48 8D 05 ? ? ? ?", operand = 3, length = 7 }
+            "#,
+        )?;
+
+        let mut offsets = Offsets::default();
+        overrides.apply(&mut offsets, &Client::new())?;
+
+        assert_eq!(offsets.network.pawn.m_iHealth, Address::from(0x344));
+        assert_eq!(offsets.interface.entity, Address::from(0x10800));
+        // 0x10020 + 7 + 0x100
+        assert_eq!(offsets.direct.local_controller, Address::from(0x10127));
+
+        assert!(overrides.contains("interface.entity"));
+        assert!(!overrides.contains("interface.convar"));
+
+        Ok(())
+    }
+}
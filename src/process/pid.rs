@@ -45,7 +45,7 @@ impl Pid {
 
     // Validates PID exists
     pub fn validate(&self) -> bool {
-        return Path::new(format!("/proc/{}", self.0).as_str()).exists();
+        Path::new(format!("/proc/{}", self.0).as_str()).exists()
     }
 }
 
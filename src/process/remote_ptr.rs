@@ -0,0 +1,182 @@
+use std::{
+    any::type_name,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+
+use anyhow::{bail, Context, Result};
+
+use super::{
+    memory::{Address, Pod},
+    source::MemorySource,
+};
+
+/// An address inside of the target process, pointing at a `T`.
+///
+/// Offsets and dereferences chain off of it, `pawn.field(m_pItemServices).deref()?`, and every
+/// step is kept in a breadcrumb so a failed read says how the address was reached:
+/// `Unable to read i32 at [pawn+0x1100]+0x40 (0x7f12...)`.
+pub struct RemotePtr<'a, M, T = ()> {
+    source: &'a M,
+    address: Address,
+    trail: String,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, M: MemorySource> RemotePtr<'a, M> {
+    /// Starts a chain at `address`, `name` is what the breadcrumb begins with
+    pub fn new(source: &'a M, address: impl Into<Address>, name: impl Into<String>) -> Self {
+        Self {
+            source,
+            address: address.into(),
+            trail: name.into(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, M: MemorySource, T> RemotePtr<'a, M, T> {
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.address.is_null()
+    }
+
+    /// The pointer `offset` bytes further on
+    pub fn field(&self, offset: impl Into<Address>) -> RemotePtr<'a, M> {
+        let offset = offset.into();
+
+        RemotePtr {
+            source: self.source,
+            address: self.address + offset,
+            trail: format!("{}+{:#x}", self.trail, u64::from(offset)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reinterprets what the pointer points at
+    pub fn cast<U>(self) -> RemotePtr<'a, M, U> {
+        RemotePtr {
+            source: self.source,
+            address: self.address,
+            trail: self.trail,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Follows the pointer stored at this address, `None` if it is null
+    pub fn try_deref(&self) -> Result<Option<RemotePtr<'a, M>>> {
+        let address = self
+            .source
+            .read::<Address>(self.address)
+            .with_context(|| format!("Unable to read pointer at {}", self))?;
+
+        Ok(address.non_null().map(|address| RemotePtr {
+            source: self.source,
+            address,
+            trail: format!("[{}]", self.trail),
+            _marker: PhantomData,
+        }))
+    }
+
+    /// Follows the pointer stored at this address, treating null as an error
+    pub fn deref(&self) -> Result<RemotePtr<'a, M>> {
+        match self.try_deref()? {
+            Some(pointer) => Ok(pointer),
+            None => bail!("Null pointer at {}", self),
+        }
+    }
+
+    /// Reads a `U` at `offset`, shorthand for `field(offset).cast::<U>().read()`
+    pub fn value<U: Pod>(&self, offset: impl Into<Address>) -> Result<U> {
+        self.field(offset).cast::<U>().read()
+    }
+
+    /// Reads the nul terminated string this points at
+    pub fn read_str(&self) -> Result<String> {
+        self.guard_null()?;
+
+        self.source
+            .read_string(self.address)
+            .with_context(|| format!("Unable to read string at {}", self))
+    }
+
+    fn guard_null(&self) -> Result<()> {
+        if self.is_null() {
+            bail!("Null pointer at {}", self);
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: MemorySource, T: Pod> RemotePtr<'_, M, T> {
+    pub fn read(&self) -> Result<T> {
+        self.guard_null()?;
+
+        self.source
+            .read::<T>(self.address)
+            .with_context(|| format!("Unable to read {} at {}", type_name::<T>(), self))
+    }
+}
+
+impl<M, T> Clone for RemotePtr<'_, M, T> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source,
+            address: self.address,
+            trail: self.trail.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M, T> Debug for RemotePtr<'_, M, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RemotePtr({})", self)
+    }
+}
+
+impl<M, T> Display for RemotePtr<'_, M, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:#x})", self.trail, u64::from(self.address))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RemotePtr;
+    use crate::process::source::test::BufferSource;
+    use anyhow::Result;
+
+    #[test]
+    fn test_chain() -> Result<()> {
+        let mut data = vec![0u8; 0x100];
+        // 0x1000 -> 0x1040, which holds an i32 and a pointer to "knife"
+        data[0x08..0x10].copy_from_slice(&0x1040u64.to_le_bytes());
+        data[0x40..0x44].copy_from_slice(&42i32.to_le_bytes());
+        data[0x48..0x50].copy_from_slice(&0x1080u64.to_le_bytes());
+        data[0x80..0x86].copy_from_slice(b"knife\0");
+
+        let source = BufferSource { base: 0x1000, data };
+        let root = RemotePtr::new(&source, 0x1000u64, "root");
+
+        let inner = root.field(0x08).deref()?;
+        assert_eq!(u64::from(inner.address()), 0x1040);
+        assert_eq!(inner.value::<i32>(0)?, 42);
+        assert_eq!(inner.field(0x08).deref()?.read_str()?, "knife");
+
+        // Null pointers are an error for deref, and nothing for try_deref
+        assert!(root.field(0x10).try_deref()?.is_none());
+        let error = root.field(0x10).deref().unwrap_err();
+        assert_eq!(error.to_string(), "Null pointer at root+0x10 (0x1010)");
+
+        // Failed reads say how the address was reached
+        let error = inner.field(0x08).deref()?.value::<u64>(0x1000).unwrap_err();
+        assert!(error.to_string().contains("[[root+0x8]+0x8]+0x1000"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, ensure, Context, Result};
+use log::debug;
+
+use super::{
+    memory::Address, offsets::LibraryOffsets, remote_ptr::RemotePtr, source::MemorySource,
+};
+use crate::constant::{CLIENT_LIB, SCHEMA_LIB};
+
+/// Name of the schema system interface exported by `libschemasystem.so`
+pub const SCHEMA_SYSTEM_INTERFACE: &str = "SchemaSystem_001";
+
+/// Upper bounds on what is walked, anything above these means the layout doesn't match the game
+const MAX_TYPE_SCOPES: u32 = 256;
+const MAX_CLASSES: usize = 65536;
+const MAX_FIELDS: i16 = 4096;
+const MAX_BASE_CLASS_DEPTH: usize = 32;
+
+/// Where things live inside of the schema system's structures. These shift between game updates
+/// far less often than the netvars do, but when they do only this needs touching.
+#[derive(Debug, Clone)]
+pub struct SchemaLayout {
+    /// `CSchemaSystem::m_TypeScopes`, a `CUtlVector<CSchemaSystemTypeScope*>`
+    pub type_scopes: u64,
+    /// `CSchemaSystemTypeScope::m_szScopeName`, an inline `char[256]`
+    pub scope_name: u64,
+    /// First bucket of `CSchemaSystemTypeScope::m_ClassBindings`, a `CUtlTSHash`
+    pub scope_class_buckets: u64,
+    pub bucket_count: u64,
+    pub bucket_stride: u64,
+    /// Head of a bucket's node chain
+    pub bucket_first_node: u64,
+    pub node_next: u64,
+    /// The `CSchemaClassInfo*` a node holds
+    pub node_data: u64,
+
+    /// `SchemaClassInfoData_t`
+    pub class_name: u64,
+    pub class_field_count: u64,
+    pub class_base_count: u64,
+    pub class_fields: u64,
+    pub class_base_classes: u64,
+
+    /// `SchemaClassFieldData_t`
+    pub field_stride: u64,
+    pub field_name: u64,
+    pub field_offset: u64,
+
+    /// `SchemaBaseClassInfoData_t`
+    pub base_class_stride: u64,
+    pub base_class_info: u64,
+}
+
+impl Default for SchemaLayout {
+    fn default() -> Self {
+        Self {
+            type_scopes: 0x1F8,
+            scope_name: 0x08,
+            scope_class_buckets: 0x5C0,
+            bucket_count: 256,
+            bucket_stride: 0x18,
+            bucket_first_node: 0x10,
+            node_next: 0x08,
+            node_data: 0x10,
+
+            class_name: 0x08,
+            class_field_count: 0x1C,
+            class_base_count: 0x23,
+            class_fields: 0x28,
+            class_base_classes: 0x30,
+
+            field_stride: 0x20,
+            field_name: 0x00,
+            field_offset: 0x10,
+
+            base_class_stride: 0x10,
+            base_class_info: 0x08,
+        }
+    }
+}
+
+/// The class bindings of one type scope of the Source 2 `SchemaSystem`, answering where a field
+/// of a class lives.
+pub struct SchemaSystem<'a, M: MemorySource> {
+    source: &'a M,
+    layout: SchemaLayout,
+    classes: HashMap<String, Address>,
+}
+
+impl<'a, M: MemorySource> SchemaSystem<'a, M> {
+    /// Finds `SchemaSystem_001` in the game and loads the client's type scope
+    pub fn from_process(source: &'a M, library_offsets: &LibraryOffsets) -> Result<Self> {
+        ensure!(
+            library_offsets.schema.is_valid(),
+            "{} is not loaded",
+            SCHEMA_LIB
+        );
+
+        let schema_system = source
+            .get_versioned_interface(library_offsets.schema.into(), SCHEMA_SYSTEM_INTERFACE)
+            .context("Unable to find the schema system interface")?;
+
+        Self::new(
+            source,
+            schema_system.into(),
+            CLIENT_LIB,
+            SchemaLayout::default(),
+        )
+    }
+
+    /// Loads every class binding of the type scope named `scope`. Scopes are named after the
+    /// module they belong to, the `.dll` name is accepted as well since some builds keep it.
+    pub fn new(
+        source: &'a M,
+        schema_system: Address,
+        scope: &str,
+        layout: SchemaLayout,
+    ) -> Result<Self> {
+        let mut schema = Self {
+            source,
+            layout,
+            classes: HashMap::new(),
+        };
+
+        let scope = schema
+            .find_scope(schema_system, scope)?
+            .with_context(|| format!("Schema system has no '{}' type scope", scope))?;
+
+        schema.load_classes(scope)?;
+
+        Ok(schema)
+    }
+
+    fn find_scope(&self, schema_system: Address, name: &str) -> Result<Option<RemotePtr<'a, M>>> {
+        let type_scopes = RemotePtr::new(self.source, schema_system, "schema_system")
+            .field(self.layout.type_scopes);
+
+        let count = type_scopes.value::<u32>(0)?;
+        ensure!(
+            count <= MAX_TYPE_SCOPES,
+            "Schema system claims {} type scopes, the layout is likely wrong",
+            count
+        );
+
+        let scopes = type_scopes.field(0x08).deref()?;
+        let dll_name = name
+            .strip_prefix("lib")
+            .map(|name| name.replace(".so", ".dll"));
+
+        for i in 0..count as u64 {
+            let scope = scopes.field(i * 8).deref()?;
+            let scope_name = scope.field(self.layout.scope_name).read_str()?;
+
+            debug!("Schema type scope: {}", scope_name);
+
+            if scope_name == name || dll_name.as_deref() == Some(scope_name.as_str()) {
+                return Ok(Some(scope));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn load_classes(&mut self, scope: RemotePtr<'a, M>) -> Result<()> {
+        let buckets = scope.field(self.layout.scope_class_buckets);
+
+        for bucket in 0..self.layout.bucket_count {
+            let mut node = buckets
+                .field(bucket * self.layout.bucket_stride + self.layout.bucket_first_node)
+                .try_deref()?;
+
+            while let Some(current) = node {
+                ensure!(
+                    self.classes.len() < MAX_CLASSES,
+                    "Type scope has over {} classes, the layout is likely wrong",
+                    MAX_CLASSES
+                );
+
+                if let Some(class) = current.field(self.layout.node_data).try_deref()? {
+                    let name = class.field(self.layout.class_name).deref()?.read_str()?;
+                    self.classes.insert(name, class.address());
+                }
+
+                node = current.field(self.layout.node_next).try_deref()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn class_names(&self) -> impl Iterator<Item = &str> {
+        self.classes.keys().map(String::as_str)
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.contains_key(class)
+    }
+
+    /// Offset of `field` inside of `class`, looking through base classes for inherited fields.
+    /// `None` if neither the class nor its bases declare it.
+    pub fn field_offset(&self, class: &str, field: &str) -> Result<Option<u32>> {
+        let &address = self
+            .classes
+            .get(class)
+            .with_context(|| format!("Schema has no class '{}'", class))?;
+
+        let name = class;
+        let mut class = Some(RemotePtr::new(self.source, address, name));
+
+        for _ in 0..MAX_BASE_CLASS_DEPTH {
+            let Some(current) = class else {
+                return Ok(None);
+            };
+
+            if let Some(offset) = self.declared_field_offset(&current, field)? {
+                return Ok(Some(offset));
+            }
+
+            // Fields of the first base share the derived class' offsets
+            class = match current.value::<u8>(self.layout.class_base_count)? {
+                0 => None,
+                _ => current
+                    .field(self.layout.class_base_classes)
+                    .deref()?
+                    .field(self.layout.base_class_info)
+                    .try_deref()?,
+            };
+        }
+
+        bail!("Base classes of '{}' nest too deeply", name)
+    }
+
+    /// A field declared by the class itself, not its bases
+    fn declared_field_offset(&self, class: &RemotePtr<'a, M>, field: &str) -> Result<Option<u32>> {
+        let count = class.value::<i16>(self.layout.class_field_count)?;
+        ensure!(
+            (0..=MAX_FIELDS).contains(&count),
+            "Class at {} claims {} fields, the layout is likely wrong",
+            class,
+            count
+        );
+
+        let Some(fields) = class.field(self.layout.class_fields).try_deref()? else {
+            return Ok(None);
+        };
+
+        for i in 0..count as u64 {
+            let entry = fields.field(i * self.layout.field_stride);
+
+            if entry.field(self.layout.field_name).deref()?.read_str()? == field {
+                return entry.value::<u32>(self.layout.field_offset).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SchemaLayout, SchemaSystem};
+    use crate::process::{
+        memory::Address,
+        source::{test::BufferSource, MemorySource},
+    };
+    use anyhow::Result;
+
+    const BASE: u64 = 0x10000;
+
+    /// A schema system laid out per the default layout inside of a flat buffer
+    struct FakeMemory {
+        source: BufferSource,
+        next: u64,
+    }
+
+    impl FakeMemory {
+        fn new() -> Self {
+            Self {
+                source: BufferSource::new(BASE, 0x8000),
+                // Past the scopes and their buckets
+                next: BASE + 0x5000,
+            }
+        }
+
+        fn alloc(&mut self, size: u64) -> u64 {
+            let address = self.next;
+            self.next += (size + 0xF) & !0xF;
+            address
+        }
+
+        fn string(&mut self, value: &str) -> u64 {
+            let address = self.alloc(value.len() as u64 + 1);
+            self.source.put(address, value.as_bytes());
+            address
+        }
+
+        /// Adds a class to a scope's bucket, chained in front of whatever is there
+        fn class(
+            &mut self,
+            scope: u64,
+            bucket: u64,
+            name: &str,
+            fields: &[(&str, u32)],
+            base: Option<u64>,
+        ) -> u64 {
+            let layout = SchemaLayout::default();
+
+            let class = self.alloc(0x40);
+            let name = self.string(name);
+            self.source.put_u64(class + layout.class_name, name);
+            self.source.put(
+                class + layout.class_field_count,
+                &(fields.len() as i16).to_le_bytes(),
+            );
+
+            let field_array = self.alloc(fields.len() as u64 * layout.field_stride);
+            for (i, (field, offset)) in fields.iter().enumerate() {
+                let entry = field_array + i as u64 * layout.field_stride;
+                let field = self.string(field);
+                self.source.put_u64(entry + layout.field_name, field);
+                self.source
+                    .put(entry + layout.field_offset, &offset.to_le_bytes());
+            }
+            self.source
+                .put_u64(class + layout.class_fields, field_array);
+
+            if let Some(base) = base {
+                let base_classes = self.alloc(layout.base_class_stride);
+                self.source
+                    .put_u64(base_classes + layout.base_class_info, base);
+                self.source
+                    .put_u64(class + layout.class_base_classes, base_classes);
+                self.source.put(class + layout.class_base_count, &[1]);
+            }
+
+            let head = scope
+                + layout.scope_class_buckets
+                + bucket * layout.bucket_stride
+                + layout.bucket_first_node;
+            let previous = self.source.read::<u64>(head).unwrap();
+
+            let node = self.alloc(0x18);
+            self.source.put_u64(node + layout.node_next, previous);
+            self.source.put_u64(node + layout.node_data, class);
+            self.source.put_u64(head, node);
+
+            class
+        }
+    }
+
+    fn fake_schema() -> BufferSource {
+        let layout = SchemaLayout::default();
+        let mut memory = FakeMemory::new();
+
+        let system = BASE;
+        let scopes = BASE + 0x400;
+        let global = BASE + 0x800;
+        let client = BASE + 0x2800;
+
+        memory
+            .source
+            .put(system + layout.type_scopes, &2u32.to_le_bytes());
+        memory
+            .source
+            .put_u64(system + layout.type_scopes + 0x08, scopes);
+        memory.source.put_u64(scopes, global);
+        memory.source.put_u64(scopes + 8, client);
+        memory
+            .source
+            .put(global + layout.scope_name, b"!GlobalTypes\0");
+        memory
+            .source
+            .put(client + layout.scope_name, b"libclient.so\0");
+
+        // Same name in another scope, which must not be picked up
+        memory.class(global, 7, "C_CSPlayerPawn", &[("m_ArmorValue", 0x1)], None);
+
+        let entity = memory.class(
+            client,
+            3,
+            "C_BaseEntity",
+            &[("m_iHealth", 0x344), ("m_iTeamNum", 0x3E3)],
+            None,
+        );
+        memory.class(
+            client,
+            3,
+            "C_CSPlayerPawn",
+            &[("m_ArmorValue", 0x2404)],
+            Some(entity),
+        );
+        memory.class(
+            client,
+            200,
+            "CCSPlayerController",
+            &[("m_iPing", 0x740)],
+            None,
+        );
+
+        memory.source
+    }
+
+    #[test]
+    fn test_field_offsets() -> Result<()> {
+        let source = fake_schema();
+        let schema = SchemaSystem::new(
+            &source,
+            Address::from(BASE),
+            "libclient.so",
+            SchemaLayout::default(),
+        )?;
+
+        assert_eq!(schema.class_names().count(), 3);
+        assert!(schema.has_class("CCSPlayerController"));
+
+        assert_eq!(
+            schema.field_offset("C_CSPlayerPawn", "m_ArmorValue")?,
+            Some(0x2404)
+        );
+        // Inherited from C_BaseEntity
+        assert_eq!(
+            schema.field_offset("C_CSPlayerPawn", "m_iHealth")?,
+            Some(0x344)
+        );
+        assert_eq!(
+            schema.field_offset("CCSPlayerController", "m_iPing")?,
+            Some(0x740)
+        );
+
+        // Fields belong to their class, not to whichever class declared the name first
+        assert_eq!(schema.field_offset("C_BaseEntity", "m_ArmorValue")?, None);
+        assert!(schema.field_offset("C_NotAClass", "m_iHealth").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_scope() {
+        let source = fake_schema();
+
+        assert!(SchemaSystem::new(
+            &source,
+            Address::from(BASE),
+            "libserver.so",
+            SchemaLayout::default()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Result};
+
+/// A byte signature with wildcards, written the way IDA prints them: `"48 83 3D ? ? ? ? ? 0F 95 C0 C3"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    bytes: Vec<Option<u8>>,
+    anchor: usize,
+}
+
+/// Bytes that show up constantly in x86-64 code, and so make poor anchors
+const COMMON_BYTES: [u8; 12] = [
+    0x00, 0xFF, 0xCC, 0x48, 0x8B, 0x89, 0x0F, 0xE8, 0x4C, 0x24, 0x83, 0x85,
+];
+
+impl Signature {
+    pub fn parse(signature: &str) -> Result<Self> {
+        let bytes = signature
+            .split_whitespace()
+            .map(|token| match token {
+                "?" | "??" => Ok(None),
+                _ => u8::from_str_radix(token, 16)
+                    .map(Some)
+                    .with_context(|| format!("Invalid signature byte '{}'", token)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::from_bytes(bytes)
+    }
+
+    /// Builds a signature from a byte array and a `"xx??x"` style mask, `?` marking wildcards.
+    pub fn from_mask(pattern: &[u8], mask: &[u8]) -> Result<Self> {
+        if pattern.len() != mask.len() {
+            bail!(
+                "Pattern is {} bytes, mask is {} bytes long. Lengths must match.",
+                pattern.len(),
+                mask.len()
+            );
+        }
+
+        Self::from_bytes(
+            pattern
+                .iter()
+                .zip(mask)
+                .map(|(&byte, &mask)| (mask == b'x').then_some(byte))
+                .collect(),
+        )
+    }
+
+    fn from_bytes(bytes: Vec<Option<u8>>) -> Result<Self> {
+        ensure!(
+            bytes.iter().any(Option::is_some),
+            "Signature needs at least one concrete byte"
+        );
+
+        // The rarest looking concrete byte, the earliest one on ties
+        let anchor = bytes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, byte)| byte.map(|byte| (i, byte)))
+            .min_by_key(|&(i, byte)| {
+                let rank = COMMON_BYTES
+                    .iter()
+                    .position(|&common| common == byte)
+                    .map_or(0, |rank| COMMON_BYTES.len() - rank);
+
+                (rank, i)
+            })
+            .map(|(i, _)| i)
+            .unwrap_or_default();
+
+        Ok(Self { bytes, anchor })
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn matches_at(&self, data: &[u8], position: usize) -> bool {
+        data.get(position..position + self.bytes.len())
+            .is_some_and(|window| {
+                window
+                    .iter()
+                    .zip(&self.bytes)
+                    .all(|(byte, expected)| expected.is_none_or(|expected| *byte == expected))
+            })
+    }
+
+    /// Offsets of every match inside of `data`. Candidates come from searching for the anchor
+    /// byte, and only those are compared against the full signature.
+    pub fn find_all(&self, data: &[u8]) -> Vec<usize> {
+        let Some(anchor_byte) = self.bytes[self.anchor] else {
+            return vec![];
+        };
+
+        if data.len() < self.bytes.len() {
+            return vec![];
+        }
+
+        // Anchor positions that leave room for the whole signature
+        let search = &data[self.anchor..=data.len() - self.bytes.len() + self.anchor];
+
+        memchr::memchr_iter(anchor_byte, search)
+            .filter(|&position| self.matches_at(data, position))
+            .collect()
+    }
+}
+
+impl FromStr for Signature {
+    type Err = anyhow::Error;
+
+    fn from_str(signature: &str) -> Result<Self> {
+        Self::parse(signature)
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+
+            match byte {
+                Some(byte) => write!(f, "{:02X}", byte)?,
+                None => write!(f, "?")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Signature;
+    use crate::process::{pid::Pid, process::ProcessHandle, source::MemorySource};
+    use anyhow::Result;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let signature = Signature::parse("48 83 3D ? ? ?? ? ? 0F 95 C0 C3")?;
+
+        assert_eq!(signature.len(), 12);
+        assert_eq!(signature.to_string(), "48 83 3D ? ? ? ? ? 0F 95 C0 C3");
+        assert_eq!(
+            signature,
+            Signature::from_mask(
+                &[0x48, 0x83, 0x3D, 0, 0, 0, 0, 0, 0x0F, 0x95, 0xC0, 0xC3],
+                b"xxx?????xxxx"
+            )?
+        );
+
+        assert!(Signature::parse("48 GG").is_err());
+        assert!(Signature::parse("? ?").is_err());
+        assert!(Signature::from_mask(&[0x48], b"xx").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_all() -> Result<()> {
+        let signature = Signature::parse("E8 ? ? 95 C3")?;
+        let data = [
+            0x95, 0xC3, 0xE8, 0x01, 0x02, 0x95, 0xC3, 0x00, 0xE8, 0xAA, 0xBB, 0x95, 0xC3, 0xE8,
+            0x01, 0x02, 0x95,
+        ];
+
+        assert_eq!(signature.find_all(&data), vec![2, 8]);
+        assert!(signature.matches_at(&data, 8));
+        assert!(!signature.matches_at(&data, 13));
+
+        assert!(signature.find_all(&data[..4]).is_empty());
+        assert!(signature.find_all(&[]).is_empty());
+
+        Ok(())
+    }
+
+    /// Scans the libc loaded into the test process for the opening bytes of `getpid`
+    #[tokio::test]
+    async fn test_scan_module() -> Result<()> {
+        let process = ProcessHandle::from_pid(Pid(std::process::id() as u64)).await?;
+        let libc = process.get_module("libc.so.6")?;
+
+        let getpid = libc::getpid as *const () as u64;
+        let bytes = process.read_bytes(getpid, 16)?;
+        let signature = Signature::parse(
+            &bytes
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| match i % 5 {
+                    4 => "?".to_string(),
+                    _ => format!("{:02X}", byte),
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        )?;
+
+        assert!(process.scan_signature(&libc, &signature)?.contains(&getpid));
+
+        // Something that appears everywhere is ambiguous, not silently the first hit
+        let ambiguous = Signature::parse("00 00")?;
+        let error = process.find_signature(&libc, &ambiguous).unwrap_err();
+        assert!(error.to_string().contains("ambiguous"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,774 @@
+use std::{path::Path, sync::Mutex};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cs2_interface::Cs2Interface;
+
+use super::{
+    maps::MemoryMap,
+    offsets::Offsets,
+    process::ProcessHandle,
+    source::{MemorySource, ReadBatch},
+};
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"MIFSNAP\0";
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// A captured copy of the memory `Cs2Interface::get_players` touched, along with the maps
+/// listing and the resolved offsets, so the same pass can be replayed without the game.
+///
+/// On disk a snapshot is the magic, a little-endian `u64` header length, a JSON header describing
+/// every region, then the raw bytes of each region back to back.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub maps: String,
+    pub offsets: Offsets,
+    pub regions: Vec<Region>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+    maps: String,
+    offsets: Offsets,
+    regions: Vec<RegionHeader>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RegionHeader {
+    address: u64,
+    length: u64,
+}
+
+impl Snapshot {
+    /// Resolves offsets against a live process and records a full `get_players` pass.
+    pub fn capture(process: ProcessHandle) -> Result<Snapshot> {
+        let maps = process.read_maps()?;
+        let offsets = Offsets::find_offsets(&process).context("Unable to resolve offsets")?;
+
+        let interface = Cs2Interface::with_offsets(RecordingSource::new(process), offsets)?;
+        interface
+            .get_players()
+            .context("Unable to record player pass")?;
+
+        let (source, offsets) = interface.into_parts();
+
+        Ok(source.into_snapshot(maps, offsets))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let header = SnapshotHeader {
+            version: SNAPSHOT_VERSION,
+            maps: self.maps.clone(),
+            offsets: self.offsets.clone(),
+            regions: self
+                .regions
+                .iter()
+                .map(|region| RegionHeader {
+                    address: region.address,
+                    length: region.bytes.len() as u64,
+                })
+                .collect(),
+        };
+
+        let header = serde_json::to_vec(&header).context("Unable to serialize snapshot header")?;
+
+        let mut bytes = Vec::with_capacity(
+            SNAPSHOT_MAGIC.len()
+                + 8
+                + header.len()
+                + self.regions.iter().map(|r| r.bytes.len()).sum::<usize>(),
+        );
+        bytes.extend_from_slice(SNAPSHOT_MAGIC);
+        bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&header);
+
+        for region in &self.regions {
+            bytes.extend_from_slice(&region.bytes);
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot> {
+        if bytes.len() < SNAPSHOT_MAGIC.len() + 8
+            || &bytes[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC
+        {
+            bail!("Not a snapshot file");
+        }
+
+        let mut cursor = SNAPSHOT_MAGIC.len();
+        let header_length = u64::from_le_bytes(
+            take(bytes, &mut cursor, 8)
+                .context("Snapshot header length is truncated")?
+                .try_into()?,
+        );
+
+        let header: SnapshotHeader = serde_json::from_slice(
+            take(bytes, &mut cursor, header_length).context("Snapshot header is truncated")?,
+        )
+        .context("Unable to parse snapshot header")?;
+
+        if header.version != SNAPSHOT_VERSION {
+            bail!("Unsupported snapshot version {}", header.version);
+        }
+
+        let mut regions = Vec::with_capacity(header.regions.len());
+
+        for region in header.regions {
+            let data = take(bytes, &mut cursor, region.length)
+                .with_context(|| format!("Region {:#x} is truncated", region.address))?;
+
+            regions.push(Region {
+                address: region.address,
+                bytes: data.to_vec(),
+            });
+        }
+
+        Ok(Snapshot {
+            maps: header.maps,
+            offsets: header.offsets,
+            regions,
+        })
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_bytes()?).context("Unable to write snapshot")
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Snapshot> {
+        Self::from_bytes(&std::fs::read(path).context("Unable to read snapshot")?)
+    }
+}
+
+/// The next `length` bytes at `cursor`, moving it past them. `None` when they run past the end,
+/// lengths come from the file and can't be trusted not to overflow.
+fn take<'a>(bytes: &'a [u8], cursor: &mut usize, length: u64) -> Option<&'a [u8]> {
+    let end = cursor.checked_add(usize::try_from(length).ok()?)?;
+    let taken = bytes.get(*cursor..end)?;
+    *cursor = end;
+
+    Some(taken)
+}
+
+/// Forwards reads to another source and keeps a copy of every byte that came back.
+pub struct RecordingSource<M: MemorySource> {
+    inner: M,
+    reads: Mutex<Vec<Region>>,
+}
+
+impl<M: MemorySource> RecordingSource<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            reads: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Merges every recorded read into non-overlapping regions and packs them into a snapshot.
+    pub fn into_snapshot(self, maps: String, offsets: Offsets) -> Snapshot {
+        let mut reads = self
+            .reads
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        reads.sort_by_key(|read| read.address);
+
+        let mut regions: Vec<Region> = vec![];
+
+        for read in reads {
+            if let Some(last) = regions.last_mut() {
+                let last_end = last.address + last.bytes.len() as u64;
+
+                if read.address <= last_end {
+                    let read_end = read.address + read.bytes.len() as u64;
+
+                    if read_end > last_end {
+                        last.bytes
+                            .extend_from_slice(&read.bytes[(last_end - read.address) as usize..]);
+                    }
+
+                    continue;
+                }
+            }
+
+            regions.push(read);
+        }
+
+        Snapshot {
+            maps,
+            offsets,
+            regions,
+        }
+    }
+}
+
+impl<M: MemorySource> MemorySource for RecordingSource<M> {
+    fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+        self.inner.read_into(address, buffer)?;
+
+        if !buffer.is_empty() {
+            self.reads
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push(Region {
+                    address,
+                    bytes: buffer.to_vec(),
+                });
+        }
+
+        Ok(())
+    }
+
+    fn memory_map(&self) -> Result<MemoryMap> {
+        self.inner.memory_map()
+    }
+
+    fn read_batch(&self, batch: &mut ReadBatch) -> usize {
+        let succeeded = self.inner.read_batch(batch);
+
+        let mut reads = self
+            .reads
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for segment in batch.segments() {
+            if segment.ok && !segment.buffer.is_empty() {
+                reads.push(Region {
+                    address: segment.address,
+                    bytes: segment.buffer.clone(),
+                });
+            }
+        }
+
+        succeeded
+    }
+}
+
+/// Serves reads back out of a `Snapshot`. Reads outside of the captured regions are errors.
+pub struct ReplaySource {
+    snapshot: Snapshot,
+}
+
+impl ReplaySource {
+    pub fn new(mut snapshot: Snapshot) -> Self {
+        snapshot.regions.sort_by_key(|region| region.address);
+
+        Self { snapshot }
+    }
+
+    pub fn offsets(&self) -> &Offsets {
+        &self.snapshot.offsets
+    }
+
+    /// Builds an interface over the snapshot using the offsets it was captured with.
+    pub fn into_interface(self) -> Result<Cs2Interface<ReplaySource>> {
+        let offsets = self.snapshot.offsets.clone();
+
+        Cs2Interface::with_offsets(self, offsets)
+    }
+}
+
+impl MemorySource for ReplaySource {
+    fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+        let regions = &self.snapshot.regions;
+        let index = regions.partition_point(|region| region.address <= address);
+
+        let region = index
+            .checked_sub(1)
+            .map(|index| &regions[index])
+            .with_context(|| format!("Address {:#x} was not captured", address))?;
+
+        let start = (address - region.address) as usize;
+        let bytes = region
+            .bytes
+            .get(start..start + buffer.len())
+            .with_context(|| {
+                format!(
+                    "Read of {} bytes at {:#x} was not captured",
+                    buffer.len(),
+                    address
+                )
+            })?;
+
+        buffer.copy_from_slice(bytes);
+
+        Ok(())
+    }
+
+    fn memory_map(&self) -> Result<MemoryMap> {
+        MemoryMap::parse(&self.snapshot.maps)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RecordingSource, Region, ReplaySource, Snapshot, SNAPSHOT_MAGIC};
+    use crate::{
+        cs2_interface::{Cs2Interface, LifeState, Player, Team, Vec3},
+        entity::{test::World, EntityHandle},
+        game_rules::{GameRules, RoundPhase},
+        process::{maps::MemoryMap, memory::Address, offsets::Offsets, source::MemorySource},
+        weapon::{Weapon, WeaponCategory},
+    };
+    use anyhow::Result;
+    use std::cell::{Cell, RefCell};
+
+    const BASE: u64 = 0x10000;
+
+    fn world_offsets() -> Offsets {
+        let mut offsets = Offsets::default();
+
+        offsets.direct.local_controller = 0x10000.into();
+        offsets.interface.convar = 0x10100.into();
+        offsets.interface.entity = 0x10200.into();
+        offsets.interface.player = 0x10210.into();
+
+        let controller = &mut offsets.network.controller;
+        controller.m_iszPlayerName = 0x10.into();
+        controller.m_hPawn = 0x18.into();
+        controller.m_iCompTeammateColor = 0x1C.into();
+        controller.m_iPing = 0x20.into();
+        controller.m_pInGameMoneyServices = 0x28.into();
+        controller.m_steamID = 0x30.into();
+
+        let pawn = &mut offsets.network.pawn;
+        pawn.m_iHealth = 0x10.into();
+        pawn.m_ArmorValue = 0x14.into();
+        pawn.m_iTeamNum = 0x18.into();
+        pawn.m_lifeState = 0x1C.into();
+        pawn.m_pClippingWeapon = 0x20.into();
+        pawn.m_vOldOrigin = 0x30.into();
+        pawn.m_angEyeAngles = 0x40.into();
+        pawn.m_pWeaponServices = 0x50.into();
+        pawn.m_pObserverServices = 0x58.into();
+        pawn.m_pItemServices = 0x60.into();
+
+        offsets.network.weapon_service.m_hActiveWeapon = 0x10.into();
+        offsets.network.weapon_service.m_hMyWeapons = 0x18.into();
+        offsets.network.weapon_service.m_iAmmo = 0x40.into();
+        offsets.network.weapon.m_iClip1 = 0x60.into();
+        offsets.network.weapon.m_pReserveAmmo = 0x64.into();
+        offsets.network.weapon.m_bInReload = 0x6C.into();
+        offsets.network.weapon.m_nSubclassID = 0x70.into();
+        offsets.network.weapon_data.m_nPrimaryAmmoType = 0x10.into();
+        offsets.network.money_service.m_iAccount = 0x10.into();
+        offsets.network.observer_service.m_hObserverTarget = 0x10.into();
+        offsets.network.econ_item.m_AttributeManager = 0x20.into();
+        offsets.network.econ_item.m_Item = 0x30.into();
+        offsets.network.econ_item.m_iItemDefinitionIndex = 0x08.into();
+        offsets.network.game_rules_proxy.m_pGameRules = 0x10.into();
+        let game_rules = &mut offsets.network.game_rules;
+        game_rules.m_bWarmupPeriod = 0x10.into();
+        game_rules.m_bFreezePeriod = 0x11.into();
+        game_rules.m_bGamePaused = 0x12.into();
+        game_rules.m_bMatchWaitingForResume = 0x13.into();
+        game_rules.m_iRoundTime = 0x14.into();
+        game_rules.m_fRoundStartTime = 0x18.into();
+        game_rules.m_totalRoundsPlayed = 0x1C.into();
+        game_rules.m_iRoundWinStatus = 0x20.into();
+        offsets.network.team.m_iTeamNum = 0x10.into();
+        offsets.network.team.m_iScore = 0x14.into();
+
+        offsets.network.item_service.m_bHasDefuser = 0x10.into();
+        offsets.network.item_service.m_bHasHelmet = 0x11.into();
+
+        offsets
+    }
+
+    /// Two players: a local terrorist holding an AK and a dead counter-terrorist
+    pub(crate) fn world_snapshot() -> Snapshot {
+        let mut world = World::new(BASE, 0x20000);
+
+        const CHUNK: u64 = 0x20000;
+
+        // Local controller pointer, entity list chunk 0
+        world.put_u64(0x10000, 0x11000);
+        world.put_chunk(0x10200, 0, CHUNK);
+
+        // Entity slots: controllers 1 and 2, pawns 100 and 101, weapons 200 to 202. Pawns were
+        // handed out with serial 1, the handles pointing at them carry it too.
+        for (index, serial, entity) in [
+            (1, 0, 0x11000),
+            (2, 0, 0x11400),
+            (100, 1, 0x11800),
+            (101, 1, 0x11C00),
+            (200, 0, 0x13000),
+            (201, 0, 0x13400),
+            (202, 0, 0x13800),
+        ] {
+            world.spawn(CHUNK, index, serial, entity, 0);
+        }
+        let pawn_handle = |index: u32| EntityHandle::new(index, 1).0;
+
+        // Weapons: entity -> identity -> designer name, the item definition index and the clip,
+        // the AK is reloading
+        for (entity, name, id, clip) in [
+            (0x13000, "weapon_knife_t", 59u16, -1i32),
+            (0x13400, "weapon_ak47", 7, 12),
+            (0x13800, "weapon_flashbang", 43, -1),
+        ] {
+            world.put(entity + 0x20 + 0x30 + 0x08, &id.to_le_bytes());
+            world.put(entity + 0x60, &clip.to_le_bytes());
+            world.put_u64(entity + 0x10, entity + 0x100);
+            world.put_u64(entity + 0x100 + 0x20, entity + 0x200);
+            world.put(entity + 0x200, format!("{}\0", name).as_bytes());
+        }
+        world.put_u32(0x13400 + 0x64, 90);
+        world.put(0x13400 + 0x6C, &[1]);
+
+        // Flashbangs count as ammo type 7 per their VData, which follows the subclass ID
+        world.put_u64(0x13800 + 0x78, 0x13800 + 0x300);
+        world.put(0x13800 + 0x300 + 0x10, &[7]);
+
+        // Game rules proxy 300 and teams 301 and 302, found by the designer names in their slots.
+        // Round 5 is live, terrorists lead 3 to 1.
+        for (index, entity, name) in [
+            (300, 0x14000, "cs_gamerules"),
+            (301, 0x14400, "cs_team_manager"),
+            (302, 0x14800, "cs_team_manager"),
+        ] {
+            world.spawn(CHUNK, index, 0, entity, entity + 0x200);
+            world.put(entity + 0x200, format!("{}\0", name).as_bytes());
+        }
+        world.put_u64(0x14000 + 0x10, 0x14100);
+        world.put_u32(0x14100 + 0x14, 115);
+        world.put(0x14100 + 0x18, &30.5f32.to_le_bytes());
+        world.put_u32(0x14100 + 0x1C, 4);
+        world.put(0x14400 + 0x10, &[2]);
+        world.put_u32(0x14400 + 0x14, 3);
+        world.put(0x14800 + 0x10, &[3]);
+        world.put_u32(0x14800 + 0x14, 1);
+
+        // Alice: controller 0x11000, pawn 0x11800
+        world.put_u64(0x11000 + 0x10, 0x12000);
+        world.put(0x12000, b"alice\0");
+        world.put_u32(0x11000 + 0x18, pawn_handle(100));
+        world.put_u32(0x11000 + 0x1C, 2);
+        world.put_u32(0x11000 + 0x20, 12);
+        world.put_u64(0x11000 + 0x28, 0x12100);
+        world.put_u32(0x12100 + 0x10, 800);
+        world.put_u64(0x11000 + 0x30, 76561198000000001);
+
+        world.put_u32(0x11800 + 0x10, 100);
+        world.put_u32(0x11800 + 0x14, 50);
+        world.put_u32(0x11800 + 0x18, 2);
+        world.put_u32(0x11800 + 0x1C, 0);
+        world.put_u64(0x11800 + 0x20, 0x13400);
+        world.put_vec3(0x11800 + 0x30, [1.0, 2.0, 3.0]);
+        world.put_vec3(0x11800 + 0x40, [4.0, 5.0, 6.0]);
+        world.put_u64(0x11800 + 0x50, 0x12200);
+        world.put_u64(0x12200 + 0x18, 3);
+        world.put_u64(0x12200 + 0x20, 0x12300);
+        world.put_u32(0x12300, 200);
+        world.put_u32(0x12304, 201);
+        world.put_u32(0x12308, 202);
+        // Two flashbangs, and a count at the index flashbangs used to have to show it isn't used
+        world.put(0x12200 + 0x40 + 7 * 2, &2u16.to_le_bytes());
+        world.put(0x12200 + 0x40 + 13 * 2, &5u16.to_le_bytes());
+        world.put_u64(0x11800 + 0x60, 0x12400);
+        world.put(0x12400 + 0x11, &[1]);
+
+        // Bob: controller 0x11400, pawn 0x11C00
+        world.put_u64(0x11400 + 0x10, 0x12800);
+        world.put(0x12800, b"bob\0");
+        world.put_u32(0x11400 + 0x18, pawn_handle(101));
+        world.put_u32(0x11400 + 0x20, 40);
+        world.put_u32(0x11C00 + 0x18, 3);
+        world.put_u32(0x11C00 + 0x1C, 2);
+
+        Snapshot {
+            maps: "00010000-00030000 rw-p 00000000 00:00 0 /fake/world.so\n".to_string(),
+            offsets: world_offsets(),
+            regions: vec![Region {
+                address: BASE,
+                bytes: world.0.data,
+            }],
+        }
+    }
+
+    fn expected_players() -> Vec<Player> {
+        let ak47 = Weapon {
+            clip: Some(12),
+            reserve_ammo: Some(90),
+            reloading: Some(true),
+            ..Weapon::identify(Some(7), Some("weapon_ak47")).unwrap()
+        };
+
+        vec![
+            Player {
+                name: "alice".to_string(),
+                health: 100,
+                armor: Some(50),
+                money: Some(800),
+                team: Team::Terrorist,
+                life_state: LifeState::Alive,
+                weapon: Some(ak47.clone()),
+                weapons: Some(vec![
+                    Weapon::identify(Some(59), Some("weapon_knife_t")).unwrap(),
+                    ak47,
+                    Weapon {
+                        count: Some(2),
+                        ..Weapon::identify(Some(43), Some("weapon_flashbang")).unwrap()
+                    },
+                ]),
+                has_defuser: Some(false),
+                has_helmet: Some(true),
+                color: Some(2),
+                position: Vec3 {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                },
+                rotation: Some(Vec3 {
+                    x: 4.0,
+                    y: 5.0,
+                    z: 6.0,
+                }),
+                ping: Some(12),
+                steam_id: Some(76561198000000001),
+                active_player: true,
+                is_local_player: true,
+            },
+            Player {
+                name: "bob".to_string(),
+                team: Team::CounterTerrorist,
+                life_state: LifeState::Dead,
+                armor: Some(0),
+                money: Some(0),
+                weapon: None,
+                weapons: Some(vec![]),
+                has_defuser: Some(false),
+                has_helmet: Some(false),
+                color: Some(0),
+                rotation: Some(Vec3::default()),
+                ping: Some(40),
+                steam_id: Some(0),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_replay_players() -> Result<()> {
+        let bytes = world_snapshot().to_bytes()?;
+        let interface = ReplaySource::new(Snapshot::from_bytes(&bytes)?).into_interface()?;
+
+        assert_eq!(interface.get_players()?, expected_players());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_game_rules() -> Result<()> {
+        let interface = ReplaySource::new(world_snapshot()).into_interface()?;
+
+        assert_eq!(
+            interface.get_game_rules()?,
+            Some(GameRules {
+                round: Some(5),
+                phase: Some(RoundPhase::Live),
+                paused: Some(false),
+                round_start_time: Some(30.5),
+                round_time: Some(115),
+                terrorist_score: Some(3),
+                counter_terrorist_score: Some(1),
+            })
+        );
+
+        // Without the game rules pointer there is nothing to read
+        let mut snapshot = world_snapshot();
+        snapshot.offsets.network.game_rules_proxy.m_pGameRules = Address::NULL;
+        let interface = ReplaySource::new(snapshot).into_interface()?;
+        assert_eq!(interface.get_game_rules()?, None);
+
+        Ok(())
+    }
+
+    /// A replay whose memory can still be changed, counting the reads made of it
+    struct Live {
+        replay: RefCell<ReplaySource>,
+        reads: Cell<usize>,
+    }
+
+    impl MemorySource for Live {
+        fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+            self.reads.set(self.reads.get() + 1);
+            self.replay.borrow().read_into(address, buffer)
+        }
+
+        fn memory_map(&self) -> Result<MemoryMap> {
+            self.replay.borrow().memory_map()
+        }
+    }
+
+    /// The entity list is walked once, after that the cached handles are enough until the proxy
+    /// changes
+    #[test]
+    fn test_game_rules_cache() -> Result<()> {
+        let interface = Cs2Interface::with_offsets(
+            Live {
+                replay: RefCell::new(ReplaySource::new(world_snapshot())),
+                reads: Cell::new(0),
+            },
+            world_offsets(),
+        )?;
+        let reads = |interface: &Cs2Interface<Live>| {
+            let before = interface.source().reads.get();
+            let rules = interface.get_game_rules().unwrap();
+            (rules, interface.source().reads.get() - before)
+        };
+
+        let (rules, walked) = reads(&interface);
+        assert_eq!(rules.as_ref().and_then(|rules| rules.round), Some(5));
+
+        let (cached_rules, cached) = reads(&interface);
+        assert_eq!(cached_rules, rules);
+        assert!(
+            cached < walked,
+            "{} reads with the cache, {} without",
+            cached,
+            walked
+        );
+
+        // The proxy's slot is reused, so its handle is stale and the list is walked again
+        let identity = 0x20000 + 120 * 300 + 0x10 - BASE;
+        interface.source().replay.borrow_mut().snapshot.regions[0].bytes
+            [identity as usize..identity as usize + 4]
+            .copy_from_slice(&EntityHandle::new(300, 2).0.to_le_bytes());
+
+        let (rewalked_rules, rewalked) = reads(&interface);
+        assert_eq!(rewalked_rules, rules);
+        assert!(rewalked > cached);
+
+        Ok(())
+    }
+
+    /// A controller whose pawn slot was reused doesn't pick up the new entity
+    #[test]
+    fn test_stale_pawn_handle() -> Result<()> {
+        let mut snapshot = world_snapshot();
+        let identity = 0x20000 + 120 * 101 + 0x10 - BASE;
+        snapshot.regions[0].bytes[identity as usize..identity as usize + 4]
+            .copy_from_slice(&EntityHandle::new(101, 2).0.to_le_bytes());
+
+        let players = ReplaySource::new(snapshot)
+            .into_interface()?
+            .get_players()?;
+
+        assert_eq!(players, expected_players()[..1]);
+
+        Ok(())
+    }
+
+    /// A garbage weapon count fails the poll instead of reading as an unarmed player
+    #[test]
+    fn test_implausible_weapon_count() {
+        let mut snapshot = world_snapshot();
+        let size = (0x12200 + 0x18 - BASE) as usize;
+        snapshot.regions[0].bytes[size..size + 8].copy_from_slice(&1000u64.to_le_bytes());
+
+        let error = ReplaySource::new(snapshot)
+            .into_interface()
+            .and_then(|interface| interface.get_players())
+            .unwrap_err();
+
+        assert!(format!("{:#}", error).contains("Implausible weapon count 1000"));
+    }
+
+    /// Optional offsets that weren't found leave their fields empty rather than reading garbage
+    #[test]
+    fn test_missing_optional_offsets() -> Result<()> {
+        let mut snapshot = world_snapshot();
+        snapshot.offsets.network.pawn.m_ArmorValue = Address::NULL;
+        snapshot.offsets.network.item_service.m_bHasHelmet = Address::NULL;
+        snapshot.offsets.network.money_service.m_iAccount = Address::NULL;
+        snapshot.offsets.network.econ_item.m_iItemDefinitionIndex = Address::NULL;
+
+        let players = ReplaySource::new(snapshot)
+            .into_interface()?
+            .get_players()?;
+        let alice = &players[0];
+
+        assert_eq!(alice.armor, None);
+        assert_eq!(alice.has_helmet, None);
+        assert_eq!(alice.money, None);
+        assert_eq!(alice.has_defuser, Some(false));
+        assert_eq!(alice.health, 100);
+
+        // Without the item definition index weapons are still known by their designer names
+        let weapon = alice.weapon.as_ref().expect("Alice holds a weapon");
+        assert_eq!(weapon.display_name, "AK-47");
+        assert_eq!(weapon.category, WeaponCategory::Rifle);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_round_trip() -> Result<()> {
+        let snapshot = world_snapshot();
+        let offsets = snapshot.offsets.clone();
+
+        // Record a pass over the full world, then replay only what was recorded
+        let recorder = RecordingSource::new(ReplaySource::new(snapshot));
+        let interface = crate::Cs2Interface::with_offsets(recorder, offsets)?;
+        let players = interface.get_players()?;
+
+        let (recorder, offsets) = interface.into_parts();
+        let recorded = recorder.into_snapshot(String::new(), offsets);
+
+        assert!(recorded.regions.len() > 1);
+        assert!(recorded
+            .regions
+            .windows(2)
+            .all(|w| w[0].address + w[0].bytes.len() as u64 <= w[1].address));
+
+        let replayed = ReplaySource::new(recorded).into_interface()?;
+        assert_eq!(replayed.get_players()?, players);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_uncaptured_read() {
+        let source = ReplaySource::new(world_snapshot());
+
+        assert!(source.read::<u32>(BASE - 4).is_err());
+        assert!(source.read::<u64>(0x2FFFC_u64).is_err());
+        assert_eq!(
+            source.get_module_base_address("world.so").unwrap(),
+            Address::from(BASE)
+        );
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert!(Snapshot::from_bytes(b"not a snapshot at all").is_err());
+
+        // Lengths that would overflow the cursor
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bytes.extend(u64::MAX.to_le_bytes());
+        assert!(Snapshot::from_bytes(&bytes).is_err());
+
+        let mut bytes = world_snapshot().to_bytes().unwrap();
+        let header_length = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) as usize;
+        let header = String::from_utf8(bytes[16..16 + header_length].to_vec()).unwrap();
+        let length = format!("\"length\":{}", 0x20000);
+        assert!(header.contains(&length));
+
+        let huge = header.replacen(&length, &format!("\"length\":{}", u64::MAX), 1);
+        bytes.splice(16..16 + header_length, huge.bytes());
+        bytes[8..16].copy_from_slice(&(huge.len() as u64).to_le_bytes());
+        assert!(Snapshot::from_bytes(&bytes).is_err());
+    }
+}
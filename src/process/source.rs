@@ -0,0 +1,729 @@
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+use serde::Serialize;
+
+use super::{
+    elf::{Elf, LoadedModule, ProgramHeader, Symbol},
+    maps::{MemoryMap, Module},
+    memory::{self, Address, Pod},
+    signature::Signature,
+};
+
+/// Upper bound on entries walked in one `CreateInterface` list before assuming it loops
+const MAX_INTERFACES: usize = 4096;
+
+/// One entry of a module's `CreateInterface` registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Interface {
+    /// Versioned name, e.g. `VEngineCvar007`
+    pub name: String,
+    /// The function creating it
+    pub factory: u64,
+    /// The interface object
+    pub address: u64,
+}
+
+impl Interface {
+    /// The version trailing the name, e.g. 7 for `VEngineCvar007`
+    pub fn version(&self) -> Option<u32> {
+        split_version(&self.name).1
+    }
+}
+
+/// Every interface one module registers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleInterfaces {
+    pub module: String,
+    pub base: u64,
+    pub interfaces: Vec<Interface>,
+}
+
+/// Splits an interface name into its base name and the version its trailing digits make up
+pub fn split_version(name: &str) -> (&str, Option<u32>) {
+    let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+
+    (base, name[base.len()..].parse().ok())
+}
+
+/// The interface registered under exactly `name`, otherwise any other version of it
+pub fn find_versioned<'a>(interfaces: &'a [Interface], name: &str) -> Option<&'a Interface> {
+    let (base_name, _) = split_version(name);
+
+    interfaces
+        .iter()
+        .find(|interface| interface.name == name)
+        .or_else(|| {
+            interfaces
+                .iter()
+                .find(|interface| split_version(&interface.name).0 == base_name)
+        })
+}
+
+/// A readable view of a target's memory.
+///
+/// `ProcessHandle` is the live implementation backed by `/proc/<pid>/mem`, but anything able to
+/// serve raw reads and module lookups (mocks, recorded dumps, other backends) can implement this
+/// and be driven by `Cs2Interface` and `Offsets::find_offsets`.
+pub trait MemorySource: Sized {
+    /// Fills `buffer` with the bytes located at `address`.
+    fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()>;
+
+    /// Retrieves the current memory map of the target.
+    fn memory_map(&self) -> Result<MemoryMap>;
+
+    /// Retrieves every mapping of a module, matched on its exact file name.
+    fn get_module(&self, module_name: &str) -> Result<Module> {
+        self.memory_map()?
+            .module(module_name)
+            .with_context(|| format!("Unable to find module {}", module_name))
+    }
+
+    /// Retrieves the base address of a specified module.
+    fn get_module_base_address(&self, module_name: &str) -> Result<Address> {
+        Ok(self
+            .get_module(module_name)
+            .context("Unable to find base address")?
+            .base())
+    }
+
+    /// Services every segment of a batch, marking each one as read or failed. Returns the number
+    /// of segments that were read successfully.
+    ///
+    /// The default issues one `read_into` per segment; sources that can do better override it.
+    fn read_batch(&self, batch: &mut ReadBatch) -> usize {
+        for segment in batch.segments.iter_mut() {
+            segment.ok = self.read_into(segment.address, &mut segment.buffer).is_ok();
+        }
+
+        batch.succeeded()
+    }
+
+    /// Walks the `CreateInterface` registry of the module loaded at `base_address`, the linked
+    /// list of `InterfaceReg` entries its export points into. `None` if the module doesn't
+    /// export `CreateInterface`.
+    fn get_interfaces(&self, base_address: u64) -> Result<Option<Vec<Interface>>> {
+        let elf = self
+            .module_elf(base_address)
+            .context("Failed to parse module ELF")?;
+
+        let Some(create_interface) = elf.lookup("CreateInterface")? else {
+            return Ok(None);
+        };
+
+        self.read_interface_registry(base_address + create_interface.value)
+            .map(Some)
+    }
+
+    /// Walks the registry a `CreateInterface` function at `create_interface` reads from
+    fn read_interface_registry(&self, create_interface: u64) -> Result<Vec<Interface>> {
+        // Compute the address of the interface entry list
+        let export_address = self.get_relative_address(create_interface, 0x01, 0x05)? + 0x10;
+
+        // Read the first interface entry
+        let mut interface_entry = self
+            .read::<u64>(self.get_relative_address(export_address, 0x03, 0x07)?)
+            .context("Failed to read the initial interface entry")?;
+
+        debug!(
+            "Resolved initial interface entry at address: {:#x}",
+            interface_entry
+        );
+
+        let mut interfaces = vec![];
+
+        // Iterate through the linked list of interface entries
+        while interface_entry != 0 {
+            if interfaces.len() >= MAX_INTERFACES {
+                bail!("Interface list at {:#x} does not end", export_address);
+            }
+
+            // Get the address of the entry's name
+            let entry_name_address = self
+                .read::<u64>(interface_entry + 8)
+                .context("Failed to read entry name address")?;
+
+            // Read the entry name as a string
+            let name = self
+                .read_string(entry_name_address)
+                .context("Failed to read entry name string")?;
+
+            // The factory is `lea rax, [rip + interface]; ret`
+            let factory = self
+                .read::<u64>(interface_entry)
+                .context("Failed to read vfunc address")?;
+
+            let address = self
+                .get_relative_address(factory, 0x03, 0x07)
+                .context("Failed to read interface offset")?;
+
+            debug!("Found interface '{}' at {:#x}", name, address);
+
+            interfaces.push(Interface {
+                name,
+                factory,
+                address,
+            });
+
+            // Move to the next entry in the linked list
+            interface_entry = self
+                .read::<u64>(interface_entry + 0x10)
+                .context("Failed to read next interface entry")?;
+        }
+
+        Ok(interfaces)
+    }
+
+    /// Lists the interfaces of every loaded module exporting `CreateInterface`
+    fn list_interfaces(&self) -> Result<Vec<ModuleInterfaces>> {
+        let mut modules = vec![];
+
+        for module in self.memory_map()?.modules() {
+            let base: u64 = module.base().into();
+
+            match self.get_interfaces(base) {
+                Ok(Some(interfaces)) => modules.push(ModuleInterfaces {
+                    module: module.name,
+                    base,
+                    interfaces,
+                }),
+                Ok(None) => {}
+                Err(e) => debug!("Skipping interfaces of {}: {:#}", module.name, e),
+            }
+        }
+
+        Ok(modules)
+    }
+
+    /// Retrieves the address of the interface registered under exactly `interface_name`, e.g.
+    /// `VEngineCvar007`.
+    fn get_interface_exact(&self, base_address: u64, interface_name: &str) -> Result<Option<u64>> {
+        Ok(self
+            .get_interfaces(base_address)?
+            .context("Module does not export CreateInterface")?
+            .into_iter()
+            .find(|interface| interface.name == interface_name)
+            .map(|interface| interface.address))
+    }
+
+    /// Retrieves the address of the first interface whose name starts with `interface_name`,
+    /// so `VEngineCvar0` matches whichever version is registered.
+    fn get_interface_offset(&self, base_address: u64, interface_name: &str) -> Result<Option<u64>> {
+        Ok(self
+            .get_interfaces(base_address)?
+            .context("Unable to resolve CreateInterface export")?
+            .into_iter()
+            .find(|interface| interface.name.starts_with(interface_name))
+            .map(|interface| interface.address))
+    }
+
+    /// Retrieves the address of a versioned interface. Falls back to any other version of it,
+    /// with a warning naming the version actually registered, so version bumps are noticed.
+    fn get_versioned_interface(&self, base_address: u64, interface_name: &str) -> Result<u64> {
+        let interfaces = self
+            .get_interfaces(base_address)?
+            .context("Unable to resolve CreateInterface export")?;
+
+        let interface = find_versioned(&interfaces, interface_name)
+            .with_context(|| format!("No version of interface {} is registered", interface_name))?;
+
+        if interface.name != interface_name {
+            warn!(
+                "Interface {} is not registered, using {} instead",
+                interface_name, interface.name
+            );
+        }
+
+        Ok(interface.address)
+    }
+
+    /// Parses the ELF image of a module loaded at `base_address`.
+    fn module_elf(&self, base_address: u64) -> Result<Elf<LoadedModule<'_, Self>>> {
+        Elf::from_module(self, base_address)
+    }
+
+    /// Retrieves the address of a specified export symbol, resolved through the module's
+    /// `DT_GNU_HASH` or `DT_HASH` table.
+    fn get_module_export(&self, base_address: u64, export_name: &str) -> Result<Option<u64>> {
+        let elf = self
+            .module_elf(base_address)
+            .context("Failed to parse module ELF")?;
+
+        match elf.lookup(export_name)? {
+            Some(symbol) => {
+                let symbol_address = symbol.value + base_address;
+
+                debug!(
+                    "Found export '{}' at address: {:#x}",
+                    export_name, symbol_address
+                );
+                Ok(Some(symbol_address))
+            }
+            None => {
+                warn!("Export '{}' not found in the module", export_name);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Lists every symbol a module exports. Values are relative to the module base.
+    fn get_module_exports(&self, base_address: u64) -> Result<Vec<Symbol>> {
+        self.module_elf(base_address)
+            .context("Failed to parse module ELF")?
+            .exports()
+    }
+
+    /// Lists every symbol a module imports from other modules.
+    fn get_module_imports(&self, base_address: u64) -> Result<Vec<Symbol>> {
+        self.module_elf(base_address)
+            .context("Failed to parse module ELF")?
+            .imports()
+    }
+
+    /// Dumps the content of a module from memory into a `Vec<u8>`, laid out by virtual address.
+    ///
+    /// Each loaded segment is read on its own so holes between segments don't cut the dump short.
+    fn dump_module(&self, address: u64) -> Result<Vec<u8>> {
+        let elf = self
+            .module_elf(address)
+            .context("Failed to parse module ELF")?;
+
+        let mut module = vec![0u8; elf.image_size() as usize];
+
+        for segment in elf.load_segments() {
+            let start = segment.p_vaddr as usize;
+            let end = start + segment.p_memsz as usize;
+
+            self.read_into(address + segment.p_vaddr, &mut module[start..end])
+                .with_context(|| {
+                    format!("Failed to read segment at {:#x}", address + segment.p_vaddr)
+                })?;
+        }
+
+        Ok(module)
+    }
+
+    /// Determines the size of a module once mapped, from the end of its highest loaded segment.
+    fn module_size(&self, base_address: u64) -> Result<u64> {
+        let module_size = self
+            .module_elf(base_address)
+            .context("Failed to parse module ELF")?
+            .image_size();
+
+        debug!(
+            "Module at {:#x} spans {:#x} bytes",
+            base_address, module_size
+        );
+
+        Ok(module_size)
+    }
+
+    /// Calculates the absolute address from a relative address in an instruction.
+    fn get_relative_address(
+        &self,
+        instruction: u64,
+        offset: u64,
+        instruction_size: u64,
+    ) -> Result<u64> {
+        // Read the 32-bit signed relative offset from the instruction
+        let rip_address = self
+            .read::<i32>(instruction + offset)
+            .context("Failed to read relative address from instruction")?;
+
+        // Calculate the resolved absolute address
+        let resolved_address = instruction
+            .wrapping_add(instruction_size)
+            .wrapping_add_signed(rip_address.into());
+
+        log::debug!(
+        "Instruction: {:#x}, Offset: {}, Instruction Size: {}, RIP Address: {}, Resolved Address: {:#x}",
+        instruction,
+        offset,
+        instruction_size,
+        rip_address,
+        resolved_address
+        );
+
+        Ok(resolved_address)
+    }
+
+    /// Retrieves the value associated with a specific tag in the dynamic section of an ELF file.
+    fn get_address_from_dynamic_section(&self, base_address: u64, tag: i64) -> Result<Option<u64>> {
+        let elf = self
+            .module_elf(base_address)
+            .context("Failed to parse module ELF")?;
+
+        Ok(elf.dynamic_value(tag))
+    }
+
+    /// Retrieves a specific program header segment in the Program Header Table (PHT)
+    /// of an ELF (Executable and Linkable Format) file based on its type.
+    fn get_segment_from_pht(&self, base_address: u64, p_type: u32) -> Result<ProgramHeader> {
+        let elf = self
+            .module_elf(base_address)
+            .context("Failed to parse module ELF")?;
+
+        elf.segment(p_type)
+            .copied()
+            .context("Tag not found in Program Header Table")
+    }
+
+    /// Scans the executable mappings of a module for every match of a signature.
+    fn scan_signature(&self, module: &Module, signature: &Signature) -> Result<Vec<u64>> {
+        let mut matches = vec![];
+
+        for region in module.executable_regions() {
+            let bytes = self
+                .read_bytes(region.start, region.size())
+                .with_context(|| {
+                    format!("Failed to read {} at {:#x}", module.name, region.start)
+                })?;
+
+            matches.extend(
+                signature
+                    .find_all(&bytes)
+                    .into_iter()
+                    .map(|offset| region.start + offset as u64),
+            );
+        }
+
+        debug!(
+            "Signature '{}' matched {} time(s) in {}",
+            signature,
+            matches.len(),
+            module.name
+        );
+
+        Ok(matches)
+    }
+
+    /// Scans a module for a signature that must match exactly once. Both a missing and an
+    /// ambiguous signature are errors, so a game update can't silently pick the wrong hit.
+    fn find_signature(&self, module: &Module, signature: &Signature) -> Result<u64> {
+        let matches = self.scan_signature(module, signature)?;
+
+        match matches.as_slice() {
+            [] => bail!("Signature '{}' not found in {}", signature, module.name),
+            [address] => Ok(*address),
+            _ => bail!(
+                "Signature '{}' is ambiguous in {}: {} matches at [{}]",
+                signature,
+                module.name,
+                matches.len(),
+                matches
+                    .iter()
+                    .map(|address| format!("{:#x}", address))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// Reads a plain-old-data value with a single contiguous read.
+    fn read<T: Pod>(&self, address: impl Into<u64>) -> Result<T> {
+        let mut value = [T::zeroed()];
+        self.read_into(address.into(), memory::bytes_of_mut(&mut value))?;
+
+        Ok(value[0])
+    }
+
+    /// Reads `count` consecutive plain-old-data values with a single contiguous read.
+    fn read_array<T: Pod>(&self, address: impl Into<u64>, count: usize) -> Result<Vec<T>> {
+        let mut values = vec![T::zeroed(); count];
+        self.read_into(address.into(), memory::bytes_of_mut(&mut values))?;
+
+        Ok(values)
+    }
+
+    /// Reads a NUL terminated string. Memory is pulled in small chunks that never cross a page
+    /// boundary, so an unmapped page after the terminator can't fail the read.
+    fn read_string(&self, address: impl Into<u64>) -> Result<String> {
+        const CHUNK_SIZE: u64 = 64;
+        const PAGE_SIZE: u64 = 0x1000;
+
+        let mut string = String::new();
+        let mut address = address.into();
+        let mut chunk = [0u8; CHUNK_SIZE as usize];
+
+        loop {
+            let length = CHUNK_SIZE.min(PAGE_SIZE - address % PAGE_SIZE) as usize;
+            let chunk = &mut chunk[..length];
+
+            if self.read_into(address, chunk).is_err() {
+                // Sources that aren't page granular may still hold the tail byte by byte
+                while let Ok(c) = self.read::<u8>(address) {
+                    if c == 0 {
+                        break;
+                    }
+
+                    string.push(c as char);
+                    address += 1;
+                }
+
+                break;
+            }
+
+            match chunk.iter().position(|&c| c == 0) {
+                Some(end) => {
+                    string.extend(chunk[..end].iter().map(|&c| c as char));
+                    break;
+                }
+                None => string.extend(chunk.iter().map(|&c| c as char)),
+            }
+
+            address += length as u64;
+        }
+
+        Ok(string)
+    }
+
+    fn read_bytes(&self, address: u64, count: u64) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; count as usize];
+        self.read_into(address, &mut buffer)?;
+
+        Ok(buffer)
+    }
+}
+
+/// One `(address, len)` request inside of a `ReadBatch`.
+#[derive(Debug, Clone)]
+pub struct ReadSegment {
+    pub address: u64,
+    pub buffer: Vec<u8>,
+    pub ok: bool,
+}
+
+/// A queue of reads that a `MemorySource` services in as few calls as it can.
+#[derive(Debug, Default, Clone)]
+pub struct ReadBatch {
+    pub(crate) segments: Vec<ReadSegment>,
+}
+
+impl ReadBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a read of `length` bytes at `address`, returning its index in the batch.
+    pub fn push(&mut self, address: impl Into<u64>, length: usize) -> usize {
+        self.segments.push(ReadSegment {
+            address: address.into(),
+            buffer: vec![0; length],
+            ok: false,
+        });
+
+        self.segments.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn segments(&self) -> &[ReadSegment] {
+        &self.segments
+    }
+
+    pub fn segments_mut(&mut self) -> &mut [ReadSegment] {
+        &mut self.segments
+    }
+
+    /// The bytes of a segment, or `None` if it failed or has not been read yet.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.segments
+            .get(index)
+            .filter(|segment| segment.ok)
+            .map(|segment| segment.buffer.as_slice())
+    }
+
+    pub fn get_u64(&self, index: usize) -> Option<u64> {
+        Some(u64::from_ne_bytes(self.get(index)?.try_into().ok()?))
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.segments.iter().filter(|segment| segment.ok).count()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{find_versioned, split_version, MemorySource};
+    use crate::process::{maps::MemoryMap, memory::Address};
+    use anyhow::{Context, Result};
+
+    /// Serves reads out of a flat buffer mapped at `base`
+    pub(crate) struct BufferSource {
+        pub base: u64,
+        pub data: Vec<u8>,
+    }
+
+    impl BufferSource {
+        /// `size` zeroed bytes at `base`
+        pub fn new(base: u64, size: usize) -> Self {
+            Self {
+                base,
+                data: vec![0; size],
+            }
+        }
+
+        /// Writes `bytes` at `address`, which has to lie inside of the buffer
+        pub fn put(&mut self, address: u64, bytes: &[u8]) {
+            let start = (address - self.base) as usize;
+            self.data[start..start + bytes.len()].copy_from_slice(bytes);
+        }
+
+        pub fn put_u32(&mut self, address: u64, value: u32) {
+            self.put(address, &value.to_le_bytes());
+        }
+
+        pub fn put_u64(&mut self, address: u64, value: u64) {
+            self.put(address, &value.to_le_bytes());
+        }
+
+        pub fn put_vec3(&mut self, address: u64, value: [f32; 3]) {
+            for (i, v) in value.iter().enumerate() {
+                self.put(address + i as u64 * 4, &v.to_le_bytes());
+            }
+        }
+    }
+
+    impl MemorySource for BufferSource {
+        fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+            let start = address
+                .checked_sub(self.base)
+                .context("Address below buffer")? as usize;
+            let bytes = self
+                .data
+                .get(start..start + buffer.len())
+                .context("Address past buffer")?;
+
+            buffer.copy_from_slice(bytes);
+            Ok(())
+        }
+
+        fn memory_map(&self) -> Result<MemoryMap> {
+            Ok(MemoryMap::default())
+        }
+    }
+
+    #[test]
+    fn test_typed_reads() -> Result<()> {
+        let mut data = vec![0u8; 0x40];
+        data[0x00..0x04].copy_from_slice(&(-5i32).to_ne_bytes());
+        data[0x08..0x10].copy_from_slice(&0x1020u64.to_ne_bytes());
+        data[0x10..0x14].copy_from_slice(&1.5f32.to_ne_bytes());
+        data[0x20..0x25].copy_from_slice(b"cs2\0x");
+
+        let source = BufferSource { base: 0x1000, data };
+
+        assert_eq!(source.read::<i32>(0x1000u64)?, -5);
+        assert_eq!(source.read::<Address>(0x1008u64)?, Address::from(0x1020));
+        assert_eq!(source.read::<f32>(0x1010u64)?, 1.5);
+        assert_eq!(source.read_string(0x1020u64)?, "cs2");
+        assert!(source.read::<u32>(0x2000u64).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_reads() -> Result<()> {
+        let data: Vec<u8> = [1u32, 2, 3, 4]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        let source = BufferSource { base: 0x1000, data };
+
+        assert_eq!(source.read_array::<u32>(0x1004u64, 3)?, vec![2, 3, 4]);
+        assert_eq!(source.read::<[u32; 2]>(0x1000u64)?, [1, 2]);
+        assert_eq!(source.read::<u64>(0x1008u64)?, 3 | (4 << 32));
+        assert!(source.read_array::<u32>(0x1008u64, 3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_address() -> Result<()> {
+        // mov rax, [rip - 0x10]
+        let mut data = vec![0u8; 0x20];
+        data[0x10..0x17].copy_from_slice(&[0x48, 0x8B, 0x05, 0xF0, 0xFF, 0xFF, 0xFF]);
+
+        let source = BufferSource { base: 0x1000, data };
+
+        assert_eq!(source.get_relative_address(0x1010, 0x03, 0x07)?, 0x1007);
+
+        Ok(())
+    }
+
+    /// A `CreateInterface` registry: a jump to the real function, whose list head is loaded 0x10
+    /// bytes in, and three entries with `lea rax, [rip + interface]; ret` factories. The head and
+    /// the interfaces sit below the code referencing them, so every displacement is negative.
+    #[test]
+    fn test_interface_registry() -> Result<()> {
+        const BASE: u64 = 0x1000;
+        const NAMES: [&str; 3] = [
+            "VEngineCvar008",
+            "GameResourceServiceClientV001",
+            "SchemaSystem_001",
+        ];
+
+        let mut source = BufferSource::new(BASE, 0x1000);
+
+        // jmp 0x1100
+        source.put(0x1000, &[0xE9, 0xFB, 0x00, 0x00, 0x00]);
+        // mov rbx, [rip - 0x97], loading the head stored at 0x1080
+        source.put(0x1110, &[0x48, 0x8B, 0x1D, 0x69, 0xFF, 0xFF, 0xFF]);
+        source.put_u64(0x1080, 0x1300);
+
+        for (i, name) in NAMES.iter().enumerate() {
+            let i = i as u64;
+            let (entry, factory, name_address) =
+                (0x1300 + i * 0x20, 0x1400 + i * 0x10, 0x1500 + i * 0x40);
+            let next = if i + 1 < NAMES.len() as u64 {
+                entry + 0x20
+            } else {
+                0
+            };
+
+            source.put_u64(entry, factory);
+            source.put_u64(entry + 0x08, name_address);
+            source.put_u64(entry + 0x10, next);
+
+            let object = 0x1180 + i * 0x40;
+            let rel = (object as i64 - (factory + 7) as i64) as i32;
+            source.put(factory, &[0x48, 0x8D, 0x05]);
+            source.put(factory + 3, &rel.to_le_bytes());
+            source.put(factory + 7, &[0xC3]);
+            source.put(name_address, format!("{}\0", name).as_bytes());
+        }
+
+        let interfaces = source.read_interface_registry(0x1000)?;
+
+        let names: Vec<&str> = interfaces.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, NAMES);
+        assert_eq!(interfaces[0].address, 0x1180);
+        assert_eq!(interfaces[2].address, 0x1200);
+        assert_eq!(interfaces[0].version(), Some(8));
+
+        // An exact name wins, a bumped version is still found
+        assert_eq!(
+            find_versioned(&interfaces, "SchemaSystem_001").map(|i| i.address),
+            Some(0x1200)
+        );
+        assert_eq!(
+            find_versioned(&interfaces, "VEngineCvar007").map(|i| i.name.as_str()),
+            Some("VEngineCvar008")
+        );
+        assert!(find_versioned(&interfaces, "Source2Client002").is_none());
+
+        assert_eq!(split_version("VEngineCvar007"), ("VEngineCvar", Some(7)));
+        assert_eq!(
+            split_version("SchemaSystem_001"),
+            ("SchemaSystem_", Some(1))
+        );
+        assert_eq!(split_version("NoVersion"), ("NoVersion", None));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,642 @@
+use std::cell::OnceCell;
+
+use anyhow::{bail, ensure, Context, Result};
+use log::{debug, warn};
+
+use super::{
+    maps::{MemoryMap, MemoryRegion},
+    memory::Address,
+    offset_report::{Conflict, DirectResolution, ResolutionMethod},
+    offsets::Offsets,
+    signature::Signature,
+    source::MemorySource,
+};
+use crate::{
+    constant::{self, CLIENT_LIB},
+    entity::EntityList,
+};
+
+/// One way of finding a direct offset
+#[derive(Debug, Clone, Copy)]
+pub enum Strategy {
+    /// The target of a RIP relative operand, `operand` bytes into a signature match in
+    /// `libclient.so`, for an instruction `length` bytes long
+    Signature {
+        pattern: &'static str,
+        operand: u64,
+        length: u64,
+    },
+    /// The pointer stored `offset` bytes into the game resource service
+    ResourcePointer { offset: u64 },
+    /// A constant distance from the entity list
+    EntityListOffset { offset: u64 },
+    /// The global in `libclient.so` holding the controller whose `m_bIsLocalPlayerController`
+    /// is set. Only works while in a match.
+    LocalControllerGlobal,
+}
+
+impl Strategy {
+    pub fn method(&self) -> ResolutionMethod {
+        match self {
+            Strategy::Signature { .. } => ResolutionMethod::Signature,
+            Strategy::ResourcePointer { .. } | Strategy::EntityListOffset { .. } => {
+                ResolutionMethod::Interface
+            }
+            Strategy::LocalControllerGlobal => ResolutionMethod::EntityList,
+        }
+    }
+}
+
+/// What a candidate has to look like before it is accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// A global in a writable segment of `libclient.so`, which may still hold null
+    ClientGlobal,
+    /// Points at readable memory
+    Readable,
+    /// Points at something laid out like the entity list, see `EntityList::validate`
+    EntityList,
+}
+
+/// A direct offset and the strategies that find it, in order of preference
+pub struct DirectOffset {
+    /// Path inside of `Offsets`, e.g. `direct.local_controller`
+    pub path: &'static str,
+    pub strategies: &'static [Strategy],
+    pub check: Check,
+}
+
+/// Every offset found by a strategy, in the order they are resolved. Later entries may derive
+/// from earlier ones.
+pub static DIRECT_OFFSETS: &[DirectOffset] = &[
+    DirectOffset {
+        path: "interface.entity",
+        strategies: &[Strategy::ResourcePointer {
+            offset: constant::ENTITY_OFFSET,
+        }],
+        check: Check::EntityList,
+    },
+    DirectOffset {
+        path: "interface.player",
+        strategies: &[Strategy::EntityListOffset { offset: 0x10 }],
+        check: Check::Readable,
+    },
+    DirectOffset {
+        path: "direct.local_controller",
+        strategies: &[
+            // cmp qword ptr [rip + local_controller], 0; setnz al; ret
+            Strategy::Signature {
+                pattern: "48 83 3D ? ? ? ? 00 0F 95 C0 C3",
+                operand: 0x03,
+                length: 0x08,
+            },
+            // The same check, with a frame pointer to restore before returning
+            Strategy::Signature {
+                pattern: "48 83 3D ? ? ? ? 00 0F 95 C0 5D C3",
+                operand: 0x03,
+                length: 0x08,
+            },
+            // mov rax, [rip + local_controller]; test rax, rax; setnz al; ret
+            Strategy::Signature {
+                pattern: "48 8B 05 ? ? ? ? 48 85 C0 0F 95 C0 C3",
+                operand: 0x03,
+                length: 0x07,
+            },
+            Strategy::LocalControllerGlobal,
+        ],
+        check: Check::ClientGlobal,
+    },
+];
+
+/// Set to anything but empty to cross-check direct offsets, see `resolve_direct_offsets_with`
+pub const CROSS_CHECK_VAR: &str = "MAKE_IT_FAIR_CROSS_CHECK";
+
+/// Resolves every entry of `DIRECT_OFFSETS` that `skip` doesn't exclude, cross-checking them if
+/// `$MAKE_IT_FAIR_CROSS_CHECK` is set
+pub fn resolve_direct_offsets(
+    offsets: &mut Offsets,
+    process: &impl MemorySource,
+    skip: impl Fn(&str) -> bool,
+) -> Vec<DirectResolution> {
+    let cross_check = std::env::var_os(CROSS_CHECK_VAR).is_some_and(|value| !value.is_empty());
+
+    resolve_direct_offsets_with(offsets, process, skip, cross_check)
+}
+
+/// Resolves every entry of `DIRECT_OFFSETS` that `skip` doesn't exclude. Strategies are tried in
+/// order and the first candidate passing the check is used. With `cross_check` the rest are run
+/// as well and candidates disagreeing with it are recorded as conflicts, at the cost of every
+/// scan they do. Entries no strategy finds are left null for validation to report. Returns how
+/// each one was found.
+pub fn resolve_direct_offsets_with(
+    offsets: &mut Offsets,
+    process: &impl MemorySource,
+    skip: impl Fn(&str) -> bool,
+    cross_check: bool,
+) -> Vec<DirectResolution> {
+    let target = Target::new(process);
+    let mut resolved = vec![];
+
+    for direct in DIRECT_OFFSETS {
+        if skip(direct.path) {
+            continue;
+        }
+
+        let mut accepted: Option<(Address, &Strategy)> = None;
+        let mut conflicts = vec![];
+
+        for strategy in direct.strategies {
+            if accepted.is_some() && !cross_check {
+                break;
+            }
+
+            let candidate = run(strategy, offsets, &target)
+                .and_then(|candidate| check(direct.check, candidate, offsets, &target));
+
+            let candidate = match candidate {
+                Ok(candidate) => candidate,
+                Err(e) => {
+                    debug!("{} via {:?} failed: {:#}", direct.path, strategy, e);
+                    continue;
+                }
+            };
+
+            match accepted {
+                None => accepted = Some((candidate, strategy)),
+                Some((value, first)) if value != candidate => {
+                    warn!(
+                        "{} via {:?} found {:#x}, disagreeing with {:#x} via {:?}",
+                        direct.path,
+                        strategy,
+                        u64::from(candidate),
+                        u64::from(value),
+                        first
+                    );
+                    conflicts.push(Conflict {
+                        method: strategy.method(),
+                        value: candidate,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        let Some((value, strategy)) = accepted else {
+            warn!("No strategy found {}", direct.path);
+            continue;
+        };
+
+        if let Some(field) = offsets.field_mut(direct.path) {
+            *field = value;
+            resolved.push(DirectResolution {
+                path: direct.path,
+                method: strategy.method(),
+                conflicts,
+            });
+        }
+    }
+
+    resolved
+}
+
+/// The process, and what strategies read of it. The memory map and the client's code and
+/// globals are read when a strategy first needs them, and then shared by all of them.
+struct Target<'a, M: MemorySource> {
+    process: &'a M,
+    map: OnceCell<MemoryMap>,
+    /// The executable mappings of `libclient.so`, by start address
+    code: OnceCell<Vec<(u64, Vec<u8>)>>,
+    /// Its writable globals, see `client_data`
+    data: OnceCell<Vec<(u64, Vec<u8>)>>,
+}
+
+impl<'a, M: MemorySource> Target<'a, M> {
+    fn new(process: &'a M) -> Self {
+        Self {
+            process,
+            map: OnceCell::new(),
+            code: OnceCell::new(),
+            data: OnceCell::new(),
+        }
+    }
+
+    fn map(&self) -> Result<&MemoryMap> {
+        get_or_try_init(&self.map, || self.process.memory_map())
+    }
+
+    fn code(&self) -> Result<&[(u64, Vec<u8>)]> {
+        get_or_try_init(&self.code, || {
+            let client = self
+                .map()?
+                .module(CLIENT_LIB)
+                .with_context(|| format!("Unable to find module {}", CLIENT_LIB))?;
+
+            self.dump(client.executable_regions())
+        })
+        .map(Vec::as_slice)
+    }
+
+    fn data(&self, client: Address) -> Result<&[(u64, Vec<u8>)]> {
+        get_or_try_init(&self.data, || {
+            self.dump(client_data(self.map()?, client)?.iter())
+        })
+        .map(Vec::as_slice)
+    }
+
+    fn dump<'r>(
+        &self,
+        regions: impl Iterator<Item = &'r MemoryRegion>,
+    ) -> Result<Vec<(u64, Vec<u8>)>> {
+        regions
+            .map(|region| {
+                let bytes = self
+                    .process
+                    .read_bytes(region.start, region.size())
+                    .with_context(|| {
+                        format!("Failed to read {} at {:#x}", CLIENT_LIB, region.start)
+                    })?;
+
+                Ok((region.start, bytes))
+            })
+            .collect()
+    }
+}
+
+/// `OnceCell::get_or_init` for an `init` that may fail, in which case nothing is stored
+fn get_or_try_init<T>(cell: &OnceCell<T>, init: impl FnOnce() -> Result<T>) -> Result<&T> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+
+    let value = init()?;
+
+    Ok(cell.get_or_init(|| value))
+}
+
+fn run(
+    strategy: &Strategy,
+    offsets: &Offsets,
+    target: &Target<impl MemorySource>,
+) -> Result<Address> {
+    let process = target.process;
+
+    match *strategy {
+        Strategy::Signature {
+            pattern,
+            operand,
+            length,
+        } => {
+            let signature = Signature::parse(pattern)?;
+            let matches: Vec<u64> = target
+                .code()?
+                .iter()
+                .flat_map(|(start, bytes)| {
+                    signature
+                        .find_all(bytes)
+                        .into_iter()
+                        .map(move |offset| start + offset as u64)
+                })
+                .collect();
+
+            // Like `MemorySource::find_signature`, a signature has to match exactly once
+            let found = match matches.as_slice() {
+                [found] => *found,
+                [] => bail!("Signature '{}' not found in {}", signature, CLIENT_LIB),
+                _ => bail!(
+                    "Signature '{}' is ambiguous in {}: {} matches",
+                    signature,
+                    CLIENT_LIB,
+                    matches.len()
+                ),
+            };
+
+            Ok(process.get_relative_address(found, operand, length)?.into())
+        }
+        Strategy::ResourcePointer { offset } => {
+            ensure!(
+                offsets.interface.resource.is_valid(),
+                "Resource service unresolved"
+            );
+
+            Ok(process.read::<Address>(offsets.interface.resource + Address::from(offset))?)
+        }
+        Strategy::EntityListOffset { offset } => {
+            ensure!(
+                offsets.interface.entity.is_valid(),
+                "Entity list unresolved"
+            );
+
+            Ok(offsets.interface.entity + Address::from(offset))
+        }
+        Strategy::LocalControllerGlobal => local_controller_global(offsets, target),
+    }
+}
+
+fn check(
+    check: Check,
+    candidate: Address,
+    offsets: &Offsets,
+    target: &Target<impl MemorySource>,
+) -> Result<Address> {
+    let process = target.process;
+
+    ensure!(!candidate.is_null(), "Found null");
+
+    match check {
+        Check::ClientGlobal => {
+            ensure!(
+                client_data(target.map()?, offsets.library.client)?
+                    .iter()
+                    .any(|region| region.contains(candidate)),
+                "Not a writable global of {}",
+                CLIENT_LIB
+            );
+        }
+        Check::Readable => {
+            process.read::<u64>(candidate).context("Not readable")?;
+        }
+        Check::EntityList => {
+            EntityList::new(process, candidate)
+                .validate()
+                .context("Not the entity list")?;
+        }
+    }
+
+    Ok(candidate)
+}
+
+/// Writable mappings of the client, including the anonymous one right after its last segment
+/// that the loader maps for `.bss`
+fn client_data(map: &MemoryMap, client: Address) -> Result<Vec<MemoryRegion>> {
+    let module = map
+        .module_containing(client)
+        .context("Client library is not mapped")?;
+
+    let mut regions: Vec<MemoryRegion> = module
+        .regions
+        .iter()
+        .filter(|region| region.permissions.write)
+        .cloned()
+        .collect();
+
+    regions.extend(
+        map.regions()
+            .iter()
+            .find(|region| region.start == module.end() && region.path.is_none())
+            .filter(|region| region.permissions.write)
+            .cloned(),
+    );
+
+    Ok(regions)
+}
+
+/// Finds the local controller through the entity list, then the one global pointing at it
+fn local_controller_global(
+    offsets: &Offsets,
+    target: &Target<impl MemorySource>,
+) -> Result<Address> {
+    let process = target.process;
+
+    let is_local = offsets.network.controller.m_bIsLocalPlayerController;
+    ensure!(is_local.is_valid(), "m_bIsLocalPlayerController unresolved");
+    ensure!(
+        offsets.interface.entity.is_valid(),
+        "Entity list unresolved"
+    );
+
+    let entities = EntityList::new(process, offsets.interface.entity);
+
+    let mut controller = None;
+    for index in 1..=64u64 {
+        let Some(entity) = entities.get(index)? else {
+            continue;
+        };
+
+        if process.read::<u8>(entity + is_local)? == 1 {
+            controller = Some(u64::from(entity));
+            break;
+        }
+    }
+
+    let controller = controller.context("No local controller in the entity list")?;
+
+    let mut globals = vec![];
+    for (start, bytes) in target.data(offsets.library.client)? {
+        globals.extend(
+            bytes
+                .chunks_exact(8)
+                .enumerate()
+                .filter(|(_, word)| u64::from_le_bytes((*word).try_into().unwrap()) == controller)
+                .map(|(i, _)| start + i as u64 * 8),
+        );
+    }
+
+    match globals.as_slice() {
+        [global] => Ok(Address::from(*global)),
+        [] => bail!("No global points at the local controller"),
+        _ => bail!("{} globals point at the local controller", globals.len()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::{resolve_direct_offsets, resolve_direct_offsets_with};
+    use crate::entity::EntityHandle;
+    use crate::process::{
+        maps::MemoryMap,
+        memory::Address,
+        offset_report::{Conflict, DirectResolution, ResolutionMethod},
+        offsets::Offsets,
+        source::MemorySource,
+    };
+    use anyhow::{Context, Result};
+
+    const CLIENT: u64 = 0x10000;
+    const DATA: u64 = 0x12000;
+    const HEAP: u64 = 0x20000;
+
+    /// `libclient.so` with code at 0x10000 and `.bss` at 0x12000, and a heap at 0x20000
+    struct Game {
+        client: Vec<u8>,
+        heap: Vec<u8>,
+        /// How often the memory map was read
+        maps: Cell<usize>,
+    }
+
+    impl Game {
+        fn write(&mut self, address: u64, bytes: &[u8]) {
+            let (memory, start) = match address {
+                HEAP.. => (&mut self.heap, address - HEAP),
+                _ => (&mut self.client, address - CLIENT),
+            };
+            memory[start as usize..start as usize + bytes.len()].copy_from_slice(bytes);
+        }
+    }
+
+    impl MemorySource for Game {
+        fn read_into(&self, address: u64, buffer: &mut [u8]) -> Result<()> {
+            let (memory, start) = match address {
+                HEAP.. => (&self.heap, address - HEAP),
+                _ => (
+                    &self.client,
+                    address.checked_sub(CLIENT).context("Unmapped")?,
+                ),
+            };
+            let bytes = memory
+                .get(start as usize..start as usize + buffer.len())
+                .context("Unmapped")?;
+            buffer.copy_from_slice(bytes);
+            Ok(())
+        }
+
+        fn memory_map(&self) -> Result<MemoryMap> {
+            self.maps.set(self.maps.get() + 1);
+            MemoryMap::parse(
+                "10000-12000 r-xp 00000000 fd:01 1 /game/libclient.so\n\
+                 12000-13000 rw-p 00000000 00:00 0\n\
+                 20000-40000 rw-p 00000000 00:00 0",
+            )
+        }
+    }
+
+    /// The resource service at HEAP, its entity list at HEAP + 0x100, one chunk at HEAP + 0x10000
+    /// holding the local controller at index 1, which lives at HEAP + 0x1800 and is pointed at by
+    /// the global at DATA + 0x40
+    fn game() -> Game {
+        let mut game = Game {
+            client: vec![0; 0x3000],
+            heap: vec![0; 0x20000],
+            maps: Cell::new(0),
+        };
+
+        game.write(HEAP + 0x50, &(HEAP + 0x100).to_le_bytes());
+        game.write(HEAP + 0x110, &(HEAP + 0x10000).to_le_bytes());
+        game.write(HEAP + 0x10000 + 120, &(HEAP + 0x1800).to_le_bytes());
+        game.write(
+            HEAP + 0x10000 + 120 + 0x10,
+            &EntityHandle::new(1, 0).0.to_le_bytes(),
+        );
+        game.write(HEAP + 0x1800 + 0x6e8, &[1]);
+        game.write(DATA + 0x40, &(HEAP + 0x1800).to_le_bytes());
+
+        game
+    }
+
+    fn method(resolved: &[DirectResolution], path: &str) -> Option<ResolutionMethod> {
+        resolved
+            .iter()
+            .find(|resolution| resolution.path == path)
+            .map(|resolution| resolution.method)
+    }
+
+    fn offsets() -> Offsets {
+        let mut offsets = Offsets::default();
+        offsets.library.client = CLIENT.into();
+        offsets.interface.resource = HEAP.into();
+        offsets.network.controller.m_bIsLocalPlayerController = 0x6e8.into();
+        offsets
+    }
+
+    #[test]
+    fn test_fallbacks() -> Result<()> {
+        let game = game();
+        let mut offsets = offsets();
+
+        // No signature matches, the schema fallback finds the global
+        let resolved = resolve_direct_offsets(&mut offsets, &game, |_| false);
+
+        assert_eq!(offsets.interface.entity, Address::from(HEAP + 0x100));
+        assert_eq!(offsets.interface.player, Address::from(HEAP + 0x110));
+        assert_eq!(offsets.direct.local_controller, Address::from(DATA + 0x40));
+        assert_eq!(
+            method(&resolved, "direct.local_controller"),
+            Some(ResolutionMethod::EntityList)
+        );
+        assert_eq!(
+            method(&resolved, "interface.entity"),
+            Some(ResolutionMethod::Interface)
+        );
+        assert!(resolved
+            .iter()
+            .all(|resolution| resolution.conflicts.is_empty()));
+
+        Ok(())
+    }
+
+    /// Another readable object at the entity list's offset isn't taken for it
+    #[test]
+    fn test_entity_list_check() -> Result<()> {
+        let mut game = game();
+        game.write(HEAP + 0x50, &(HEAP + 0x200).to_le_bytes());
+        game.write(HEAP + 0x210, &(HEAP + 0x400).to_le_bytes());
+        game.write(HEAP + 0x400 + 120, &(HEAP + 0x1800).to_le_bytes());
+
+        let mut offsets = offsets();
+        resolve_direct_offsets(&mut offsets, &game, |_| false);
+
+        assert!(offsets.interface.entity.is_null());
+        assert!(offsets.interface.player.is_null());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_preferred() -> Result<()> {
+        let mut game = game();
+        // cmp qword ptr [rip + 0x1fe8], 0; setnz al; ret at 0x10050, referencing DATA + 0x40
+        game.write(
+            CLIENT + 0x50,
+            &[
+                0x48, 0x83, 0x3D, 0xE8, 0x1F, 0x00, 0x00, 0x00, 0x0F, 0x95, 0xC0, 0xC3,
+            ],
+        );
+
+        let mut offsets = offsets();
+        let resolved = resolve_direct_offsets(&mut offsets, &game, |_| false);
+
+        assert_eq!(offsets.direct.local_controller, Address::from(DATA + 0x40));
+        assert_eq!(
+            method(&resolved, "direct.local_controller"),
+            Some(ResolutionMethod::Signature)
+        );
+
+        // A match pointing outside of the client's data is rejected
+        game.write(CLIENT + 0x53, &0x100u32.to_le_bytes());
+        let mut offsets = self::offsets();
+        resolve_direct_offsets(&mut offsets, &game, |_| false);
+        assert_eq!(offsets.direct.local_controller, Address::from(DATA + 0x40));
+
+        // A match at another global wins, the entity list isn't even searched
+        game.write(CLIENT + 0x53, &0x2028u32.to_le_bytes());
+        let mut offsets = self::offsets();
+        let resolved = resolve_direct_offsets_with(&mut offsets, &game, |_| false, false);
+        assert_eq!(offsets.direct.local_controller, Address::from(DATA + 0x80));
+        assert!(resolved
+            .iter()
+            .all(|resolution| resolution.conflicts.is_empty()));
+
+        // Cross-checking keeps the entity list's disagreement, reading the maps only once
+        game.maps.set(0);
+        let mut offsets = self::offsets();
+        let resolved = resolve_direct_offsets_with(&mut offsets, &game, |_| false, true);
+        assert_eq!(offsets.direct.local_controller, Address::from(DATA + 0x80));
+        assert_eq!(game.maps.get(), 1);
+
+        let local_controller = resolved
+            .iter()
+            .find(|resolution| resolution.path == "direct.local_controller")
+            .expect("local controller was resolved");
+        assert_eq!(local_controller.method, ResolutionMethod::Signature);
+        assert_eq!(
+            local_controller.conflicts,
+            vec![Conflict {
+                method: ResolutionMethod::EntityList,
+                value: Address::from(DATA + 0x40),
+            }]
+        );
+
+        Ok(())
+    }
+}
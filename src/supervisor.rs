@@ -0,0 +1,206 @@
+use std::{thread, time::Duration};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::{runtime::Runtime, sync::watch};
+
+use crate::{constant, Cs2Interface, Pid, ProcessHandle};
+
+/// Where the supervisor is in the lifecycle of the game process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state")]
+pub enum SupervisorState {
+    /// No matching process is running yet
+    WaitingForProcess,
+    /// Found the process, opening it and resolving offsets
+    Attaching { pid: u64 },
+    /// Offsets are resolved and the process is being polled
+    Attached { pid: u64 },
+    /// Lost the process or gave up on it, a new attach follows
+    Detached { reason: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    pub process_name: String,
+    /// Delay between polls while attached
+    pub poll_interval: Duration,
+    /// Delay between attempts to find or attach to the process
+    pub retry_interval: Duration,
+    /// Failed polls in a row, with the process still alive, before offsets are re-resolved
+    pub max_consecutive_errors: u32,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            process_name: constant::PROCESS_NAME.to_string(),
+            poll_interval: Duration::from_millis(100),
+            retry_interval: Duration::from_secs(2),
+            max_consecutive_errors: 10,
+        }
+    }
+}
+
+/// Keeps a `Cs2Interface` attached to the game across restarts.
+///
+/// It waits for the process, attaches and resolves offsets, then polls until the process exits or
+/// reads keep failing, at which point it starts over. Every transition is published on a watch
+/// channel.
+pub struct Supervisor {
+    config: SupervisorConfig,
+    state: watch::Sender<SupervisorState>,
+}
+
+impl Supervisor {
+    pub fn new(config: SupervisorConfig) -> Self {
+        let (state, _) = watch::channel(SupervisorState::WaitingForProcess);
+
+        Self { config, state }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<SupervisorState> {
+        self.state.subscribe()
+    }
+
+    pub fn state(&self) -> SupervisorState {
+        self.state.borrow().clone()
+    }
+
+    fn set_state(&self, state: SupervisorState) {
+        self.state.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+
+            info!("Supervisor state: {:?}", state);
+            *current = state;
+            true
+        });
+    }
+
+    /// Runs forever on the calling thread, calling `on_poll` every poll interval while attached.
+    /// Resolving offsets and polling block, so this belongs on a thread of its own rather than on
+    /// an async runtime. An error from `on_poll` counts towards the reattach threshold. Only
+    /// returns if the runtime finding the process can't be built.
+    pub fn run<F>(&self, mut on_poll: F) -> Result<()>
+    where
+        F: FnMut(&Cs2Interface) -> Result<()>,
+    {
+        // Finding and opening the process is async, nothing else is
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Unable to build the supervisor's runtime")?;
+
+        loop {
+            let interface = self.attach(&runtime);
+            let pid = interface.source().pid.0;
+
+            self.set_state(SupervisorState::Attached { pid });
+
+            let reason = self.poll(&interface, &mut on_poll);
+
+            warn!("Detached from process {}: {}", pid, reason);
+            self.set_state(SupervisorState::Detached { reason });
+
+            thread::sleep(self.config.retry_interval);
+        }
+    }
+
+    /// Waits for the process and keeps trying until offsets resolve
+    fn attach(&self, runtime: &Runtime) -> Cs2Interface {
+        loop {
+            let pid = match runtime.block_on(Pid::from_process_name(&self.config.process_name)) {
+                Ok(pid) => pid,
+                Err(_) => {
+                    self.set_state(SupervisorState::WaitingForProcess);
+                    thread::sleep(self.config.retry_interval);
+                    continue;
+                }
+            };
+
+            self.set_state(SupervisorState::Attaching { pid: pid.0 });
+
+            match Self::open(runtime, pid) {
+                Ok(interface) => return interface,
+                Err(e) => {
+                    self.set_state(SupervisorState::Detached {
+                        reason: format!("{:#}", e),
+                    });
+                    thread::sleep(self.config.retry_interval);
+                }
+            }
+        }
+    }
+
+    fn open(runtime: &Runtime, pid: Pid) -> Result<Cs2Interface> {
+        let process = runtime.block_on(ProcessHandle::from_pid(pid))?;
+
+        Cs2Interface::new(process).context("Unable to resolve offsets")
+    }
+
+    /// Polls until the process goes away or fails too often, returning why it stopped
+    fn poll<F>(&self, interface: &Cs2Interface, on_poll: &mut F) -> String
+    where
+        F: FnMut(&Cs2Interface) -> Result<()>,
+    {
+        let pid = &interface.source().pid;
+        let mut consecutive_errors = 0;
+
+        loop {
+            if !pid.validate() {
+                return "Process exited".to_string();
+            }
+
+            match on_poll(interface) {
+                Ok(()) => consecutive_errors = 0,
+                Err(e) => {
+                    if !pid.validate() {
+                        return "Process exited".to_string();
+                    }
+
+                    consecutive_errors += 1;
+                    warn!(
+                        "Poll failed ({}/{}): {:#}",
+                        consecutive_errors, self.config.max_consecutive_errors, e
+                    );
+
+                    if consecutive_errors >= self.config.max_consecutive_errors {
+                        return format!("Too many failed polls, last error: {:#}", e);
+                    }
+                }
+            }
+
+            thread::sleep(self.config.poll_interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, thread, time::Duration};
+
+    use super::{Supervisor, SupervisorConfig, SupervisorState};
+
+    /// Without the process the supervisor waits instead of bailing
+    #[test]
+    fn test_waits_for_process() {
+        let supervisor = Arc::new(Supervisor::new(SupervisorConfig {
+            process_name: "make_it_fair_no_such_process".to_string(),
+            retry_interval: Duration::from_millis(10),
+            ..Default::default()
+        }));
+        let state = supervisor.subscribe();
+
+        let run = thread::spawn({
+            let supervisor = supervisor.clone();
+            move || supervisor.run(|_| panic!("Nothing to poll"))
+        });
+        thread::sleep(Duration::from_millis(100));
+        assert!(!run.is_finished());
+
+        assert_eq!(*state.borrow(), SupervisorState::WaitingForProcess);
+    }
+}
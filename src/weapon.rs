@@ -0,0 +1,234 @@
+use serde::Serialize;
+
+/// What kind of item a weapon is
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum WeaponCategory {
+    Knife,
+    Pistol,
+    Smg,
+    Rifle,
+    Sniper,
+    Shotgun,
+    MachineGun,
+    Grenade,
+    C4,
+    /// The Zeus
+    Equipment,
+    #[default]
+    Unknown,
+}
+
+/// Which loadout slot a weapon is carried in, `gear_slot_t`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum WeaponSlot {
+    Primary,
+    Secondary,
+    Knife,
+    Grenade,
+    C4,
+    #[default]
+    Unknown,
+}
+
+/// A weapon as identified by its item definition index, and how loaded it is
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Weapon {
+    /// `m_iItemDefinitionIndex`, 0 when it couldn't be read
+    pub id: u16,
+    /// The entity's designer name, e.g. `weapon_ak47`
+    pub name: String,
+    /// e.g. `AK-47`
+    pub display_name: String,
+    pub category: WeaponCategory,
+    pub slot: WeaponSlot,
+    /// Buy menu price, 0 for what can't be bought
+    pub price: u32,
+    /// Rounds in the magazine, `None` for weapons without one such as knives and grenades
+    pub clip: Option<i32>,
+    /// Rounds carried for it outside of the magazine
+    pub reserve_ammo: Option<i32>,
+    pub reloading: Option<bool>,
+    /// How many of a grenade are carried, they all share one entity
+    pub count: Option<u16>,
+}
+
+/// One row of `WEAPONS`
+struct WeaponInfo {
+    id: u16,
+    name: &'static str,
+    display_name: &'static str,
+    category: WeaponCategory,
+    price: u32,
+}
+
+/// Item definitions from `items_game.txt`. Several weapons share an entity class, and so a
+/// designer name, with another: the USP-S is a `weapon_hkp2000`, the M4A1-S a `weapon_m4a1`, the
+/// CZ75-Auto a `weapon_p250`, the R8 a `weapon_deagle` and the MP5-SD a `weapon_mp7`. Only the
+/// item definition index tells them apart.
+static WEAPONS: &[WeaponInfo] = {
+    use WeaponCategory::*;
+
+    macro_rules! weapons {
+        ($(($id:literal, $name:literal, $display_name:literal, $category:ident, $price:literal),)*) => {
+            &[$(WeaponInfo {
+                id: $id,
+                name: $name,
+                display_name: $display_name,
+                category: $category,
+                price: $price,
+            },)*]
+        };
+    }
+
+    weapons![
+        (1, "weapon_deagle", "Desert Eagle", Pistol, 700),
+        (2, "weapon_elite", "Dual Berettas", Pistol, 300),
+        (3, "weapon_fiveseven", "Five-SeveN", Pistol, 500),
+        (4, "weapon_glock", "Glock-18", Pistol, 200),
+        (7, "weapon_ak47", "AK-47", Rifle, 2700),
+        (8, "weapon_aug", "AUG", Rifle, 3300),
+        (9, "weapon_awp", "AWP", Sniper, 4750),
+        (10, "weapon_famas", "FAMAS", Rifle, 2050),
+        (11, "weapon_g3sg1", "G3SG1", Sniper, 5000),
+        (13, "weapon_galilar", "Galil AR", Rifle, 1800),
+        (14, "weapon_m249", "M249", MachineGun, 5200),
+        (16, "weapon_m4a1", "M4A4", Rifle, 3100),
+        (17, "weapon_mac10", "MAC-10", Smg, 1050),
+        (19, "weapon_p90", "P90", Smg, 2350),
+        (23, "weapon_mp5sd", "MP5-SD", Smg, 1500),
+        (24, "weapon_ump45", "UMP-45", Smg, 1200),
+        (25, "weapon_xm1014", "XM1014", Shotgun, 2000),
+        (26, "weapon_bizon", "PP-Bizon", Smg, 1400),
+        (27, "weapon_mag7", "MAG-7", Shotgun, 1300),
+        (28, "weapon_negev", "Negev", MachineGun, 1700),
+        (29, "weapon_sawedoff", "Sawed-Off", Shotgun, 1100),
+        (30, "weapon_tec9", "Tec-9", Pistol, 500),
+        (31, "weapon_taser", "Zeus x27", Equipment, 200),
+        (32, "weapon_hkp2000", "P2000", Pistol, 200),
+        (33, "weapon_mp7", "MP7", Smg, 1500),
+        (34, "weapon_mp9", "MP9", Smg, 1250),
+        (35, "weapon_nova", "Nova", Shotgun, 1050),
+        (36, "weapon_p250", "P250", Pistol, 300),
+        (38, "weapon_scar20", "SCAR-20", Sniper, 5000),
+        (39, "weapon_sg556", "SG 553", Rifle, 3000),
+        (40, "weapon_ssg08", "SSG 08", Sniper, 1700),
+        (41, "weapon_knifegg", "Golden Knife", Knife, 0),
+        (42, "weapon_knife", "Knife", Knife, 0),
+        (43, "weapon_flashbang", "Flashbang", Grenade, 200),
+        (44, "weapon_hegrenade", "HE Grenade", Grenade, 300),
+        (45, "weapon_smokegrenade", "Smoke Grenade", Grenade, 300),
+        (46, "weapon_molotov", "Molotov", Grenade, 400),
+        (47, "weapon_decoy", "Decoy Grenade", Grenade, 50),
+        (48, "weapon_incgrenade", "Incendiary Grenade", Grenade, 500),
+        (49, "weapon_c4", "C4 Explosive", C4, 0),
+        (59, "weapon_knife_t", "Knife", Knife, 0),
+        (60, "weapon_m4a1_silencer", "M4A1-S", Rifle, 2900),
+        (61, "weapon_usp_silencer", "USP-S", Pistol, 200),
+        (63, "weapon_cz75a", "CZ75-Auto", Pistol, 500),
+        (64, "weapon_revolver", "R8 Revolver", Pistol, 600),
+    ]
+};
+
+/// Finishes and skins of knives get definition indices of their own, all in this range
+const KNIVES: std::ops::RangeInclusive<u16> = 500..=599;
+
+impl WeaponCategory {
+    pub fn slot(self) -> WeaponSlot {
+        match self {
+            WeaponCategory::Smg
+            | WeaponCategory::Rifle
+            | WeaponCategory::Sniper
+            | WeaponCategory::Shotgun
+            | WeaponCategory::MachineGun => WeaponSlot::Primary,
+            WeaponCategory::Pistol => WeaponSlot::Secondary,
+            WeaponCategory::Knife | WeaponCategory::Equipment => WeaponSlot::Knife,
+            WeaponCategory::Grenade => WeaponSlot::Grenade,
+            WeaponCategory::C4 => WeaponSlot::C4,
+            WeaponCategory::Unknown => WeaponSlot::Unknown,
+        }
+    }
+}
+
+impl Weapon {
+    /// Looks a weapon up by item definition index, falling back to its designer name when the
+    /// index is missing or not in the table. A weapon known by neither is kept, uncategorized,
+    /// under its designer name.
+    pub fn identify(id: Option<u16>, name: Option<&str>) -> Option<Weapon> {
+        let by_id = id.and_then(|id| WEAPONS.iter().find(|info| info.id == id));
+        let by_name = || name.and_then(|name| WEAPONS.iter().find(|info| info.name == name));
+
+        if let Some(info) = by_id.or_else(by_name) {
+            return Some(Weapon {
+                id: id.unwrap_or(info.id),
+                name: name.unwrap_or(info.name).to_string(),
+                display_name: info.display_name.to_string(),
+                category: info.category,
+                slot: info.category.slot(),
+                price: info.price,
+                ..Default::default()
+            });
+        }
+
+        let id = id.unwrap_or_default();
+
+        if KNIVES.contains(&id) {
+            return Some(Weapon {
+                id,
+                name: name.unwrap_or("weapon_knife").to_string(),
+                display_name: "Knife".to_string(),
+                category: WeaponCategory::Knife,
+                slot: WeaponSlot::Knife,
+                ..Default::default()
+            });
+        }
+
+        name.map(|name| Weapon {
+            id,
+            name: name.to_string(),
+            display_name: name.trim_start_matches("weapon_").to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{Weapon, WeaponCategory, WeaponSlot, WEAPONS};
+
+    #[test]
+    fn test_table() {
+        let ids: HashSet<u16> = WEAPONS.iter().map(|info| info.id).collect();
+        assert_eq!(ids.len(), WEAPONS.len(), "Definition indices are unique");
+    }
+
+    #[test]
+    fn test_identify() {
+        let ak = Weapon::identify(Some(7), Some("weapon_ak47")).unwrap();
+        assert_eq!(ak.display_name, "AK-47");
+        assert_eq!(ak.category, WeaponCategory::Rifle);
+        assert_eq!(ak.slot, WeaponSlot::Primary);
+        assert_eq!(ak.price, 2700);
+
+        // The index wins over a designer name shared between weapons
+        let usp = Weapon::identify(Some(61), Some("weapon_hkp2000")).unwrap();
+        assert_eq!(usp.display_name, "USP-S");
+        assert_eq!(usp.name, "weapon_hkp2000");
+        assert_eq!(usp.slot, WeaponSlot::Secondary);
+
+        // Without the index the designer name is all there is
+        let p2000 = Weapon::identify(None, Some("weapon_hkp2000")).unwrap();
+        assert_eq!(p2000.display_name, "P2000");
+        assert_eq!(p2000.id, 32);
+
+        let karambit = Weapon::identify(Some(507), Some("weapon_knife")).unwrap();
+        assert_eq!(karambit.category, WeaponCategory::Knife);
+
+        let unknown = Weapon::identify(Some(9999), Some("weapon_snowball")).unwrap();
+        assert_eq!(unknown.category, WeaponCategory::Unknown);
+        assert_eq!(unknown.display_name, "snowball");
+
+        assert_eq!(Weapon::identify(None, None), None);
+    }
+}